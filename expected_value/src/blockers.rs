@@ -0,0 +1,183 @@
+use std::fmt;
+use crate::{Card, CardSuit, HandRank, get_best_hand_string};
+use crate::score_table::{ScoreTable, card_index};
+
+// hand class of every opponent combo that can be dealt from deck, on the board
+fn count_ranks(deck: &[Card], board: &[Card], score_table: &ScoreTable) -> [u32; 10] {
+  let mut counts = [0; 10];
+  let mut cards: Vec<u8> = vec![0, 0];
+  cards.extend(board.iter().map(card_index));
+  for i in 0..deck.len() {
+    for j in (i+1)..deck.len() {
+      cards[0] = card_index(&deck[i]);
+      cards[1] = card_index(&deck[j]);
+      counts[get_best_hand_string(score_table.best_score(&cards)) as usize - 1] += 1;
+    }
+  }
+  return counts
+}
+
+fn count_combos(deck: &[Card], f: impl Fn(&Card, &Card) -> bool) -> u32 {
+  let mut n = 0;
+  for i in 0..deck.len() {
+    for j in (i+1)..deck.len() {
+      if f(&deck[i], &deck[j]) {
+        n += 1;
+      }
+    }
+  }
+  return n
+}
+
+fn rank_from_index(i: usize) -> HandRank {
+  return get_best_hand_string(i as f32*100.0)
+}
+
+pub struct Blocker {
+  pub card: Card,
+  pub description: String,
+  // opponent combos of that kind without and with hero's cards removed
+  pub before: u32,
+  pub after: u32,
+}
+
+pub struct BlockerAnalysis {
+  pub blockers: Vec<Blocker>,
+  // opponent combos in each hand class without and with hero's cards removed, from worst to best
+  pub ranks: Vec<(HandRank, u32, u32)>,
+  pub total_before: u32,
+  pub total_after: u32,
+}
+
+impl BlockerAnalysis {
+  // classes that make up a smaller share of opponent's range because of hero's cards
+  pub fn shrinking_ranks(&self) -> Vec<(HandRank, f32)> {
+    let mut res = Vec::new();
+    for (rank, before, after) in &self.ranks {
+      let share_before = *before as f32/self.total_before as f32;
+      let share_after = *after as f32/self.total_after as f32;
+      if share_after < share_before {
+        res.push((*rank, share_after/share_before - 1.0));
+      }
+    }
+    return res
+  }
+}
+
+// Card removal: opponent combos are enumerated the same way as for the opponent hand range, once as if hero's cards
+// were still in the deck and once without them. The difference is what hero's cards block.
+// card_deck is the live deck, board and dead cards already out of it or removed here.
+pub fn analyze_blockers(hand: &Vec<Card>, board: &[Card], card_deck: &[Card], score_table: &ScoreTable) -> BlockerAnalysis {
+  let deck_before: Vec<Card> = card_deck.iter().filter(|c| !board.contains(c)).cloned().collect();
+  let deck_after: Vec<Card> = deck_before.iter().filter(|c| !hand.contains(c)).cloned().collect();
+  let counts_before = count_ranks(&deck_before, board, score_table);
+  let counts_after = count_ranks(&deck_after, board, score_table);
+  let mut ranks = Vec::new();
+  for i in 0..10 {
+    if counts_before[i] > 0 {
+      ranks.push((rank_from_index(i), counts_before[i], counts_after[i]));
+    }
+  }
+
+  let mut blockers = Vec::new();
+  let mut add = |card: Card, description: String, f: &dyn Fn(&Card, &Card) -> bool| {
+    let before = count_combos(&deck_before, f);
+    let after = count_combos(&deck_after, f);
+    if after < before {
+      blockers.push(Blocker{card: card, description: description, before: before, after: after});
+    }
+  };
+  // one line per rank, a pocket pair would list the same combos twice. only one card of a suit can be the nut one
+  let mut paired_ranks = Vec::new();
+  for card in hand {
+    // flush is possible with 3 of a suit on board, a draw with 2 before the river
+    let suited_on_board = board.iter().filter(|c| c.suit == card.suit).count();
+    if suited_on_board >= 3 || (suited_on_board == 2 && board.len() < 5) {
+      let nut_rank = (2..=14).rev().find(|r| !board.iter().any(|c| c.suit == card.suit && c.rank == *r)).unwrap();
+      if card.rank == nut_rank {
+        let kind = if suited_on_board >= 3 { "nut flush" } else { "nut flush draw" };
+        let suit = card.suit;
+        add(*card, format!("{} blocker ({})", kind, suit_name(suit)), &|a, b| a.suit == suit && b.suit == suit);
+      }
+    }
+    if board.iter().any(|c| c.rank == card.rank) && !paired_ranks.contains(&card.rank) {
+      paired_ranks.push(card.rank);
+      let rank = card.rank;
+      add(*card, format!("blocks sets of {}", card_rank_name(rank)), &|a, b| a.rank == rank && b.rank == rank);
+    }
+  }
+  // two pair with both hole cards pairing the board, at least one of them on a rank hero pairs
+  if let Some(card) = hand.iter().find(|c| paired_ranks.contains(&c.rank)) {
+    let names: Vec<String> = paired_ranks.iter().map(|r| card_rank_name(*r).to_string()).collect();
+    add(*card, format!("blocks two pair with {}", names.join(", ")), &|a, b| {
+      a.rank != b.rank && (paired_ranks.contains(&a.rank) || paired_ranks.contains(&b.rank))
+        && board.iter().any(|c| c.rank == a.rank) && board.iter().any(|c| c.rank == b.rank)
+    });
+  }
+  return BlockerAnalysis{
+    blockers: blockers,
+    ranks: ranks,
+    total_before: counts_before.iter().sum(),
+    total_after: counts_after.iter().sum(),
+  }
+}
+
+fn suit_name(suit: CardSuit) -> &'static str {
+  return match suit {
+    CardSuit::Heart => "hearts",
+    CardSuit::Spade => "spades",
+    CardSuit::Club => "clubs",
+    CardSuit::Diamond => "diamonds",
+  }
+}
+
+fn card_rank_name(rank: u8) -> char {
+  return b"??23456789TJQKA"[rank as usize] as char
+}
+
+impl fmt::Display for BlockerAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "Blockers:")?;
+    if self.blockers.is_empty() {
+      writeln!(f, "  none")?;
+    }
+    for b in &self.blockers {
+      writeln!(f, "  {}: {} ({} -> {} combos)", b.card, b.description, b.before, b.after)?;
+    }
+    writeln!(f, "Opponent combos by class ({} -> {} with hero's cards removed):", self.total_before, self.total_after)?;
+    let shrinking = self.shrinking_ranks();
+    for (rank, before, after) in &self.ranks {
+      let change = match shrinking.iter().find(|(r, _)| r == rank) {
+        Some((_, c)) => format!("  share {:+.1}%", c*100.0),
+        None => String::new(),
+      };
+      writeln!(f, "{:<20}:{:>5} -> {:>5}{}", rank.to_string(), before, after, change)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::{NUM_CARDS, index_to_card};
+  use crate::score_table::tests::holdem_score_table;
+
+  fn descriptions(hand: &str, board: &str) -> Vec<String> {
+    let deck: Vec<Card> = (0..NUM_CARDS as u8).map(index_to_card).collect();
+    let hand = conv_string_to_cards(hand);
+    let board = conv_string_to_cards(board);
+    let deck: Vec<Card> = deck.into_iter().filter(|c| !hand.contains(c) && !board.contains(c)).collect();
+    let mut deck_with_hand = deck.clone();
+    deck_with_hand.extend(hand.iter());
+    return analyze_blockers(&hand, &board, &deck_with_hand, holdem_score_table()).blockers.iter().map(|b| b.description.clone()).collect()
+  }
+
+  #[test]
+  fn one_line_per_rank_and_suit() {
+    // pocket pair with a set on board, and two hole cards of the flush suit
+    assert_eq!(descriptions("Ah Ad", "As 9h 7h"), vec!["nut flush draw blocker (hearts)", "blocks sets of A", "blocks two pair with A"]);
+    assert_eq!(descriptions("Ah 9d", "As 9h 7c"), vec!["blocks sets of A", "blocks sets of 9", "blocks two pair with A, 9"]);
+  }
+}
@@ -0,0 +1,325 @@
+use std::fmt;
+use serde::Serialize;
+use crate::Card;
+
+#[derive(PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardPairing {
+  Unpaired = 0,
+  Paired = 1,
+  TwoPaired = 2,
+  Trips = 3,
+  FullHouse = 4,
+  Quads = 5,
+}
+
+impl fmt::Display for BoardPairing {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BoardPairing::Unpaired => write!(f, "unpaired"),
+      BoardPairing::Paired => write!(f, "paired"),
+      BoardPairing::TwoPaired => write!(f, "two-paired"),
+      BoardPairing::Trips => write!(f, "trips"),
+      BoardPairing::FullHouse => write!(f, "full house"),
+      BoardPairing::Quads => write!(f, "quads"),
+    }
+  }
+}
+
+// TwoTone - two cards of one suit, DoubleTwoTone - two cards of two suits (turn/river),
+// ThreeFlush/FourFlush - that many cards of a suit without whole board being suited.
+#[derive(PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuitPattern {
+  Rainbow = 0,
+  TwoTone = 1,
+  DoubleTwoTone = 2,
+  ThreeFlush = 3,
+  FourFlush = 4,
+  Monotone = 5,
+}
+
+impl fmt::Display for SuitPattern {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SuitPattern::Rainbow => write!(f, "rainbow"),
+      SuitPattern::TwoTone => write!(f, "two-tone"),
+      SuitPattern::DoubleTwoTone => write!(f, "double two-tone"),
+      SuitPattern::ThreeFlush => write!(f, "three-flush"),
+      SuitPattern::FourFlush => write!(f, "four-flush"),
+      SuitPattern::Monotone => write!(f, "monotone"),
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectedness {
+  Disconnected = 0,
+  SemiConnected = 1,
+  Connected = 2,
+}
+
+impl fmt::Display for Connectedness {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Connectedness::Disconnected => write!(f, "disconnected"),
+      Connectedness::SemiConnected => write!(f, "semi-connected"),
+      Connectedness::Connected => write!(f, "connected"),
+    }
+  }
+}
+
+// Broadway - highest card is T, J, Q or K. Middle - 7, 8 or 9. Low - 6 and below.
+#[derive(PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HighCardClass {
+  Low = 0,
+  Middle = 1,
+  Broadway = 2,
+  AceHigh = 3,
+}
+
+impl fmt::Display for HighCardClass {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      HighCardClass::Low => write!(f, "low"),
+      HighCardClass::Middle => write!(f, "middle"),
+      HighCardClass::Broadway => write!(f, "broadway"),
+      HighCardClass::AceHigh => write!(f, "ace high"),
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub struct BoardTexture {
+  pub num_cards: usize,
+  pub pairing: BoardPairing,
+  pub suit_pattern: SuitPattern,
+  pub max_suit_count: usize,
+  // someone holding two cards of the suit already has a flush
+  pub flush_possible: bool,
+  // a single card of the suit makes a flush
+  pub one_card_flush: bool,
+  // there are cards to come and one more card of the suit gives two suited hole cards a flush draw
+  pub flush_draw_possible: bool,
+  // rainbow flop, only runner-runner flushes are possible
+  pub backdoor_flush_only: bool,
+  // number of distinct hole card rank pairs (ignoring suits) that make a straight right now
+  pub straight_combos: usize,
+  pub straight_possible: bool,
+  // a single card of some rank makes a straight
+  pub one_card_straight: bool,
+  // there are cards to come and at least one 5 rank window has 2 board ranks in it
+  pub straight_draw_possible: bool,
+  // the most distinct board ranks that fit into any 5 rank window
+  pub max_ranks_in_window: usize,
+  pub connectedness: Connectedness,
+  pub high_card: u8,
+  pub high_card_class: HighCardClass,
+  pub num_broadway_cards: usize,
+}
+
+impl fmt::Display for BoardTexture {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}, {}, {}, {}", self.pairing, self.suit_pattern, self.connectedness, self.high_card_class)?;
+    if self.one_card_flush {
+      write!(f, ", one card flush")?;
+    } else if self.flush_possible {
+      write!(f, ", flush possible")?;
+    } else if self.flush_draw_possible {
+      write!(f, ", flush draw possible")?;
+    } else if self.backdoor_flush_only {
+      write!(f, ", backdoor flush only")?;
+    }
+    if self.one_card_straight {
+      write!(f, ", one card straight")?;
+    } else if self.straight_possible {
+      write!(f, ", straight possible ({} combos)", self.straight_combos)?;
+    } else if self.straight_draw_possible {
+      write!(f, ", straight draw possible")?;
+    }
+    Ok(())
+  }
+}
+
+// ranks present on board, ace is also counted as 1 for wheel straights. index is rank [1..14]
+fn rank_presence(ranks: &Vec<u8>) -> [bool; 15] {
+  let mut present = [false; 15];
+  for r in ranks {
+    present[*r as usize] = true;
+    if *r == 14 {
+      present[1] = true;
+    }
+  }
+  return present
+}
+
+fn makes_straight(present: &[bool; 15]) -> bool {
+  for low in 1..=10 {
+    if (low..low+5).all(|r| present[r]) {
+      return true
+    }
+  }
+  return false
+}
+
+pub fn analyze_board(community: &Vec<Card>) -> BoardTexture {
+  let num_cards = community.len();
+  let cards_to_come = num_cards < 5;
+
+  let mut rank_counts = [0usize; 15];
+  let mut suit_counts = [0usize; 5];
+  for c in community {
+    rank_counts[c.rank as usize] += 1;
+    suit_counts[c.suit as usize] += 1;
+  }
+
+  let num_pairs = rank_counts.iter().filter(|&&n| n == 2).count();
+  let num_trips = rank_counts.iter().filter(|&&n| n == 3).count();
+  let num_quads = rank_counts.iter().filter(|&&n| n == 4).count();
+  let pairing = if num_quads > 0 {
+    BoardPairing::Quads
+  } else if num_trips > 0 && num_pairs > 0 {
+    BoardPairing::FullHouse
+  } else if num_trips > 0 {
+    BoardPairing::Trips
+  } else if num_pairs >= 2 {
+    BoardPairing::TwoPaired
+  } else if num_pairs == 1 {
+    BoardPairing::Paired
+  } else {
+    BoardPairing::Unpaired
+  };
+
+  let max_suit_count = *suit_counts.iter().max().unwrap();
+  let num_two_suits = suit_counts.iter().filter(|&&n| n == 2).count();
+  let suit_pattern = if max_suit_count == num_cards {
+    SuitPattern::Monotone
+  } else if max_suit_count >= 4 {
+    SuitPattern::FourFlush
+  } else if max_suit_count == 3 {
+    SuitPattern::ThreeFlush
+  } else if num_two_suits >= 2 {
+    SuitPattern::DoubleTwoTone
+  } else if max_suit_count == 2 {
+    SuitPattern::TwoTone
+  } else {
+    SuitPattern::Rainbow
+  };
+
+  let mut ranks: Vec<u8> = community.iter().map(|c| c.rank).collect();
+  ranks.sort();
+  ranks.dedup();
+  let present = rank_presence(&ranks);
+
+  // enumerate every hole card rank pair to see which ones complete a straight
+  let mut straight_combos = 0;
+  let mut one_card_straight = false;
+  for r1 in 2..=14u8 {
+    let mut with_one = present;
+    with_one[r1 as usize] = true;
+    if r1 == 14 {
+      with_one[1] = true;
+    }
+    if makes_straight(&with_one) && !makes_straight(&present) {
+      one_card_straight = true;
+    }
+    for r2 in (r1+1)..=14u8 {
+      let mut with_two = with_one;
+      with_two[r2 as usize] = true;
+      if r2 == 14 {
+        with_two[1] = true;
+      }
+      if makes_straight(&with_two) {
+        straight_combos += 1;
+      }
+    }
+  }
+
+  let mut max_ranks_in_window = 0;
+  for low in 1..=10 {
+    let n = (low..low+5).filter(|&r| present[r]).count();
+    if n > max_ranks_in_window {
+      max_ranks_in_window = n;
+    }
+  }
+  let connectedness = if max_ranks_in_window >= 3 {
+    Connectedness::Connected
+  } else if max_ranks_in_window == 2 {
+    Connectedness::SemiConnected
+  } else {
+    Connectedness::Disconnected
+  };
+
+  let high_card = *ranks.last().unwrap_or(&0);
+  let high_card_class = match high_card {
+    14 => HighCardClass::AceHigh,
+    10..=13 => HighCardClass::Broadway,
+    7..=9 => HighCardClass::Middle,
+    _ => HighCardClass::Low,
+  };
+
+  return BoardTexture {
+    num_cards: num_cards,
+    pairing: pairing,
+    suit_pattern: suit_pattern,
+    max_suit_count: max_suit_count,
+    flush_possible: max_suit_count >= 3,
+    one_card_flush: max_suit_count >= 4,
+    flush_draw_possible: cards_to_come && max_suit_count == 2,
+    backdoor_flush_only: num_cards == 3 && max_suit_count == 1,
+    straight_combos: straight_combos,
+    straight_possible: straight_combos > 0,
+    one_card_straight: one_card_straight,
+    straight_draw_possible: cards_to_come && max_ranks_in_window >= 2,
+    max_ranks_in_window: max_ranks_in_window,
+    connectedness: connectedness,
+    high_card: high_card,
+    high_card_class: high_card_class,
+    num_broadway_cards: community.iter().filter(|c| c.rank >= 10).count(),
+  }
+}
+
+impl BoardTexture {
+  // one line of JSON with every field, enums as snake_case names
+  pub fn to_json(&self) -> String {
+    return serde_json::to_string(self).unwrap()
+  }
+
+  pub fn ml_header() -> Vec<&'static str> {
+    return vec!["board_pairing", "board_suit_pattern", "board_max_suit", "board_flush_possible", "board_one_card_flush",
+      "board_straight_combos", "board_one_card_straight", "board_connectedness", "board_high_card", "board_broadway_cards"]
+  }
+
+  // numeric features for generate_ml_data, in the same order as ml_header()
+  pub fn ml_features(&self) -> Vec<String> {
+    return vec![
+      (self.pairing as u8).to_string(),
+      (self.suit_pattern as u8).to_string(),
+      self.max_suit_count.to_string(),
+      (self.flush_possible as u8).to_string(),
+      (self.one_card_flush as u8).to_string(),
+      self.straight_combos.to_string(),
+      (self.one_card_straight as u8).to_string(),
+      (self.connectedness as u8).to_string(),
+      self.high_card.to_string(),
+      self.num_broadway_cards.to_string(),
+    ]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+
+  #[test]
+  fn json_output() {
+    let texture = analyze_board(&conv_string_to_cards("Th 9h 9c 2s"));
+    let json = texture.to_json();
+    assert!(json.starts_with("{\"num_cards\":4,\"pairing\":\"paired\",\"suit_pattern\":\"two_tone\""), "{}", json);
+    assert!(json.contains("\"connectedness\":\"semi_connected\""), "{}", json);
+    assert!(json.contains("\"high_card_class\":\"broadway\""), "{}", json);
+  }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use crate::{Card, HandRank, TablePosition, is_in_position_range, is_early_position_range, is_late_position_range};
+use crate::contribution::hero_hand_rank;
+use crate::game_engine::{Agent, GameView, PlayerAction};
+use crate::hand_history::{Street, position_from_button};
+use crate::score_table::{ScoreTable, card_index};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum BotKind {
+  Random,
+  CallingStation,
+  Chart,
+  EquityThreshold,
+  PotOdds,
+}
+
+impl fmt::Display for BotKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BotKind::Random => write!(f, "random"),
+      BotKind::CallingStation => write!(f, "station"),
+      BotKind::Chart => write!(f, "chart"),
+      BotKind::EquityThreshold => write!(f, "equity"),
+      BotKind::PotOdds => write!(f, "potodds"),
+    }
+  }
+}
+
+impl FromStr for BotKind {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    return match s.to_lowercase().as_str() {
+      "random" => Ok(BotKind::Random),
+      "station" => Ok(BotKind::CallingStation),
+      "chart" => Ok(BotKind::Chart),
+      "equity" => Ok(BotKind::EquityThreshold),
+      "potodds" => Ok(BotKind::PotOdds),
+      _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown bot: {}", s))),
+    }
+  }
+}
+
+// what the bots need from the crate to evaluate hands
+pub struct BotContext<'a> {
+  pub combinations: &'a HashMap<Vec<Card>, (f32,f32)>,
+  pub starting_hands: &'a HashMap<Vec<Card>, (f32,f32,f32)>,
+  pub card_deck: &'a Vec<Card>,
+  pub score_table: &'a ScoreTable,
+}
+
+pub fn create_bot<'a>(kind: BotKind, ctx: &'a BotContext<'a>, rng: ChaCha20Rng) -> Box<dyn Agent + 'a> {
+  return match kind {
+    BotKind::Random => Box::new(RandomBot{rng: rng}),
+    BotKind::CallingStation => Box::new(CallingStationBot{}),
+    BotKind::Chart => Box::new(ChartBot{ctx: ctx}),
+    BotKind::EquityThreshold => Box::new(EquityThresholdBot{ctx: ctx}),
+    BotKind::PotOdds => Box::new(PotOddsBot{ctx: ctx}),
+  }
+}
+
+// bet or raise to a total amount, clamped to what is legal. calls when raising isn't allowed
+fn raise_to(view: &GameView, to: u32) -> PlayerAction {
+  if !view.can_raise() {
+    return PlayerAction::Call
+  }
+  let to = to.clamp(view.min_raise_to, view.max_raise_to);
+  if to == view.max_raise_to {
+    return PlayerAction::AllIn
+  }
+  return PlayerAction::Raise(to)
+}
+
+// raise by a share of the pot after calling
+fn pot_raise(view: &GameView, share: f32) -> PlayerAction {
+  let current_bet = view.street_bets.iter().max().cloned().unwrap_or(0);
+  let pot_after_call = view.pot + view.to_call;
+  return raise_to(view, current_bet + (pot_after_call as f32*share) as u32)
+}
+
+fn check_or_fold(view: &GameView) -> PlayerAction {
+  return if view.to_call == 0 { PlayerAction::Check } else { PlayerAction::Fold }
+}
+
+fn sorted_hand(view: &GameView) -> Vec<Card> {
+  let mut hand = view.hole_cards.clone();
+  hand.sort();
+  return hand
+}
+
+// preflop position of the seat, counting only players dealt into the hand
+fn view_position(view: &GameView) -> Option<TablePosition> {
+  let dealt: Vec<usize> = (1..=view.num_seats).map(|i| (view.button+i) % view.num_seats)
+    .filter(|s| view.stacks[*s] > 0 || view.total_bets[*s] > 0).collect();
+  let i = dealt.iter().position(|s| *s == view.seat)?;
+  return position_from_button(i, dealt.len())
+}
+
+// hero's equity: average preflop equity of the hand, or share of opponent's two card combos hero beats on the board
+// after the flop (ties count half). score table lookups keep it cheap enough for every decision of every hand
+fn view_equity(view: &GameView, ctx: &BotContext) -> f32 {
+  let hand = sorted_hand(view);
+  if view.board.is_empty() {
+    return ctx.starting_hands[&hand].1
+  }
+  let live: Vec<u8> = ctx.card_deck.iter().filter(|c| !hand.contains(c) && !view.board.contains(c)).map(card_index).collect();
+  let mut cards: Vec<u8> = hand.iter().chain(view.board.iter()).map(card_index).collect();
+  let hero_score = ctx.score_table.best_score(&cards);
+  let mut won = 0.0;
+  let mut total = 0;
+  for i in 0..live.len() {
+    for j in i+1..live.len() {
+      cards[0] = live[i];
+      cards[1] = live[j];
+      let score = ctx.score_table.best_score(&cards);
+      if hero_score > score {
+        won += 1.0;
+      } else if hero_score == score {
+        won += 0.5;
+      }
+      total += 1;
+    }
+  }
+  return won/total as f32
+}
+
+pub struct RandomBot {
+  rng: ChaCha20Rng,
+}
+
+impl Agent for RandomBot {
+  fn name(&self) -> String {
+    return "random".to_string()
+  }
+
+  fn act(&mut self, view: &GameView) -> PlayerAction {
+    return match self.rng.gen_range(0..4) {
+      0 => check_or_fold(view),
+      1 => PlayerAction::Call,
+      2 => pot_raise(view, 0.5),
+      _ => if view.to_call == 0 { PlayerAction::Check } else { PlayerAction::Call },
+    }
+  }
+}
+
+pub struct CallingStationBot {}
+
+impl Agent for CallingStationBot {
+  fn name(&self) -> String {
+    return "station".to_string()
+  }
+
+  fn act(&mut self, _view: &GameView) -> PlayerAction {
+    return PlayerAction::Call
+  }
+}
+
+// opens the hands from is_*_position_range, reraises the early position range.
+// after the flop bets two pairs and better, calls up to half pot with a pair
+pub struct ChartBot<'a> {
+  ctx: &'a BotContext<'a>,
+}
+
+impl Agent for ChartBot<'_> {
+  fn name(&self) -> String {
+    return "chart".to_string()
+  }
+
+  fn act(&mut self, view: &GameView) -> PlayerAction {
+    let hand = sorted_hand(view);
+    if view.street == Street::Preflop {
+      let big_blind = view.config.big_blind;
+      let raised = view.street_bets.iter().any(|b| *b > big_blind);
+      // big blind has no opening range, it defends with the late position one
+      let in_range = match view_position(view) {
+        Some(pos) => is_in_position_range(pos, &hand),
+        None => is_late_position_range(&hand),
+      };
+      if !in_range {
+        return check_or_fold(view)
+      }
+      if !raised {
+        return raise_to(view, big_blind*3)
+      }
+      if is_early_position_range(&hand) {
+        return pot_raise(view, 1.0)
+      }
+      return PlayerAction::Call
+    }
+
+    let rank = hero_hand_rank(&hand, view.board, self.ctx.combinations);
+    if rank >= HandRank::TwoPairs {
+      return pot_raise(view, 0.75)
+    }
+    if rank == HandRank::Pair && view.to_call*2 <= view.pot {
+      return PlayerAction::Call
+    }
+    return check_or_fold(view)
+  }
+}
+
+// fixed equity thresholds: raise with strong hands, call with medium ones, give up the rest
+pub struct EquityThresholdBot<'a> {
+  ctx: &'a BotContext<'a>,
+}
+
+impl Agent for EquityThresholdBot<'_> {
+  fn name(&self) -> String {
+    return "equity".to_string()
+  }
+
+  fn act(&mut self, view: &GameView) -> PlayerAction {
+    let equity = view_equity(view, self.ctx);
+    // preflop average equity is much flatter than postflop equity
+    let (raise_eq, call_eq) = if view.street == Street::Preflop { (0.60, 0.50) } else { (0.80, 0.55) };
+    if equity >= raise_eq {
+      return pot_raise(view, 0.5)
+    }
+    if equity >= call_eq {
+      return PlayerAction::Call
+    }
+    return check_or_fold(view)
+  }
+}
+
+// calls whenever equity beats the price, bets for value when well ahead
+pub struct PotOddsBot<'a> {
+  ctx: &'a BotContext<'a>,
+}
+
+impl Agent for PotOddsBot<'_> {
+  fn name(&self) -> String {
+    return "potodds".to_string()
+  }
+
+  fn act(&mut self, view: &GameView) -> PlayerAction {
+    let equity = view_equity(view, self.ctx);
+    let value_eq = if view.street == Street::Preflop { 0.62 } else { 0.85 };
+    if equity >= value_eq {
+      return pot_raise(view, 0.66)
+    }
+    if view.to_call == 0 {
+      return PlayerAction::Check
+    }
+    let pot_odds = view.to_call as f32/(view.pot + view.to_call) as f32;
+    if equity >= pot_odds {
+      return PlayerAction::Call
+    }
+    return PlayerAction::Fold
+  }
+}
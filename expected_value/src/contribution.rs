@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::{Card, HandRank, get_best_hand, get_best_hand_string, is_hand_part_of_made_up_hand};
+use crate::score_table::{ScoreTable, card_index};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ContributionKind {
+  // no hole card is part of the best hand, hero is playing the board
+  BoardPlays,
+  // hole cards only add kickers to a hand that comes from the board
+  KickersOnly,
+  // hole cards are part of what makes the hand rank
+  MadeHand,
+}
+
+impl fmt::Display for ContributionKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ContributionKind::BoardPlays => write!(f, "board plays"),
+      ContributionKind::KickersOnly => write!(f, "kickers only"),
+      ContributionKind::MadeHand => write!(f, "made hand"),
+    }
+  }
+}
+
+// how hero's hand compares to the 5 card board on its own
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum VersusBoard {
+  Ties,
+  BetterKicker,
+  BetterRank,
+}
+
+impl fmt::Display for VersusBoard {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VersusBoard::Ties => write!(f, "ties the board"),
+      VersusBoard::BetterKicker => write!(f, "beats the board by kicker"),
+      VersusBoard::BetterRank => write!(f, "beats the board"),
+    }
+  }
+}
+
+pub struct HeroContribution {
+  pub hand_rank: HandRank,
+  pub assembled_hand: Vec<Card>,
+  // hole cards that are in the best 5 card hand and can't be replaced by a board card
+  pub playing_hole_cards: Vec<Card>,
+  pub kind: ContributionKind,
+  // only known on the river, board alone is not a 5 card hand before that
+  pub versus_board: Option<VersusBoard>,
+  // opponent combos that end up with the same HandRank: (better, tie, worse).
+  // when hand comes from the board this is the kicker battle.
+  pub same_rank_opponents: (u32, u32, u32),
+}
+
+impl fmt::Display for HeroContribution {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let cards: Vec<String> = self.playing_hole_cards.iter().map(|c| c.to_string()).collect();
+    if !cards.is_empty() {
+      write!(f, "Hole cards: {} play ({})", cards.join(" "), self.kind)?;
+    } else {
+      write!(f, "Hole cards: none play ({})", self.kind)?;
+    }
+    if let Some(vs) = self.versus_board {
+      write!(f, ", {}", vs)?;
+    }
+    let (better, tie, worse) = self.same_rank_opponents;
+    let total = better + tie + worse;
+    if total > 0 {
+      write!(f, "\nSame hand rank opponents: {:.1}% better, {:.1}% tie, {:.1}% worse",
+        better as f32/total as f32*100.0, tie as f32/total as f32*100.0, worse as f32/total as f32*100.0)?;
+    }
+    Ok(())
+  }
+}
+
+// rank of the hand that hero makes with hole cards. if hole cards don't play or are only kickers,
+// the hole cards haven't made anything by themselves and this is HighCard.
+pub fn hero_hand_rank(hand: &Vec<Card>, community: &Vec<Card>, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> HandRank {
+  let (_, _, rank, assembled_hand) = get_best_hand(hand, community, combinations);
+  if is_hand_part_of_made_up_hand(hand, &rank, &assembled_hand) {
+    return rank
+  }
+  return HandRank::HighCard
+}
+
+pub fn analyze_contribution(hand: &Vec<Card>, community: &Vec<Card>, card_deck: &[Card], combinations: &HashMap<Vec<Card>,(f32,f32)>, score_table: &ScoreTable) -> HeroContribution {
+  let (hero_score, _, hand_rank, assembled_hand) = get_best_hand(hand, community, combinations);
+  let playing_hole_cards: Vec<Card> = hand.iter().filter(|c| assembled_hand.contains(c)).cloned().collect();
+  let kind = if playing_hole_cards.is_empty() {
+    ContributionKind::BoardPlays
+  } else if is_hand_part_of_made_up_hand(hand, &hand_rank, &assembled_hand) {
+    ContributionKind::MadeHand
+  } else {
+    ContributionKind::KickersOnly
+  };
+
+  let mut versus_board = None;
+  if community.len() == 5 {
+    let mut board = community.clone();
+    board.sort();
+    let (board_score, _) = combinations[&board];
+    versus_board = Some(if hero_score <= board_score {
+      VersusBoard::Ties
+    } else if get_best_hand_string(board_score) == hand_rank {
+      VersusBoard::BetterKicker
+    } else {
+      VersusBoard::BetterRank
+    });
+  }
+
+  // compare against every opponent combo that ends up with the same hand rank
+  let mut remaining_deck = card_deck.to_vec();
+  remaining_deck.retain(|x| !hand.contains(x) && !community.contains(x));
+  let mut opp_cards: Vec<u8> = vec![0, 0];
+  opp_cards.extend(community.iter().map(card_index));
+  let mut same_rank_opponents = (0, 0, 0);
+  // opponent hands need at least 5 cards to be evaluated
+  if community.len() >= 3 {
+    for i in 0..remaining_deck.len() {
+      for j in (i+1)..remaining_deck.len() {
+        opp_cards[0] = card_index(&remaining_deck[i]);
+        opp_cards[1] = card_index(&remaining_deck[j]);
+        let opp_score = score_table.best_score(&opp_cards);
+        if get_best_hand_string(opp_score) != hand_rank {
+          continue;
+        }
+        if opp_score > hero_score {
+          same_rank_opponents.0 += 1;
+        } else if opp_score == hero_score {
+          same_rank_opponents.1 += 1;
+        } else {
+          same_rank_opponents.2 += 1;
+        }
+      }
+    }
+  }
+
+  return HeroContribution {
+    hand_rank: hand_rank,
+    assembled_hand: assembled_hand,
+    playing_hole_cards: playing_hole_cards,
+    kind: kind,
+    versus_board: versus_board,
+    same_rank_opponents: same_rank_opponents,
+  }
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+use itertools::Itertools;
+use crate::{Card, HandRank, get_best_hand_string};
+use crate::score_table::{ScoreTable, card_index};
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum DrawType {
+  FlushDraw,
+  NutFlushDraw,
+  OpenEndedStraightDraw,
+  DoubleGutshot,
+  Gutshot,
+  BackdoorFlushDraw,
+  BackdoorStraightDraw,
+  Overcards,
+}
+
+impl fmt::Display for DrawType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DrawType::FlushDraw => write!(f, "flush draw"),
+      DrawType::NutFlushDraw => write!(f, "nut flush draw"),
+      DrawType::OpenEndedStraightDraw => write!(f, "OESD"),
+      DrawType::DoubleGutshot => write!(f, "double gutshot"),
+      DrawType::Gutshot => write!(f, "gutshot"),
+      DrawType::BackdoorFlushDraw => write!(f, "backdoor flush draw"),
+      DrawType::BackdoorStraightDraw => write!(f, "backdoor straight draw"),
+      DrawType::Overcards => write!(f, "overcards"),
+    }
+  }
+}
+
+impl DrawType {
+  fn is_flush_draw(&self) -> bool {
+    return *self == DrawType::FlushDraw || *self == DrawType::NutFlushDraw
+  }
+
+  fn is_straight_draw(&self) -> bool {
+    return *self == DrawType::OpenEndedStraightDraw || *self == DrawType::DoubleGutshot || *self == DrawType::Gutshot
+  }
+}
+
+// why an out is "dirty": after it falls some opponent combos beat what hero makes with it
+#[derive(Clone, Copy)]
+pub struct DirtyReason {
+  // combos that beat hero out of every combo left in the deck
+  pub beaten_by: u32,
+  pub total: u32,
+  // hand class of the best of them
+  pub best: HandRank,
+}
+
+impl fmt::Display for DirtyReason {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}/{} combos beat it, best: {}", self.beaten_by, self.total, self.best)
+  }
+}
+
+pub struct Out {
+  pub card: Card,
+  // draws completed by this card
+  pub completes: Vec<DrawType>,
+  pub dirty: Option<DirtyReason>,
+}
+
+pub struct DrawAnalysis {
+  pub draws: Vec<DrawType>,
+  pub outs: Vec<Out>,
+}
+
+impl DrawAnalysis {
+  pub fn is_combo_draw(&self) -> bool {
+    return self.draws.iter().any(|d| d.is_flush_draw()) && self.draws.iter().any(|d| d.is_straight_draw())
+  }
+
+  pub fn num_clean_outs(&self) -> usize {
+    return self.outs.iter().filter(|o| o.dirty.is_none()).count()
+  }
+}
+
+impl fmt::Display for DrawAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let names: Vec<String> = self.draws.iter().map(|d| d.to_string()).collect();
+    write!(f, "Draws: {}", names.join(", "))?;
+    if self.is_combo_draw() {
+      write!(f, " (combo draw)")?;
+    }
+    if self.outs.is_empty() {
+      return Ok(())
+    }
+    let cards: Vec<String> = self.outs.iter().map(|o| o.card.to_string()).collect();
+    write!(f, "\nOuts ({}, {} clean): {}", self.outs.len(), self.num_clean_outs(), cards.join(" "))?;
+    for o in &self.outs {
+      if let Some(reason) = o.dirty {
+        write!(f, "\n  dirty: {} ({})", o.card, reason)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn set_rank(present: &mut [bool; 15], rank: u8) {
+  present[rank as usize] = true;
+  if rank == 14 {
+    present[1] = true;
+  }
+}
+
+// highest card of the best straight, ace plays low in the wheel
+fn straight_high(present: &[bool; 15]) -> Option<u8> {
+  for high in (5..=14).rev() {
+    if (high-4..=high).all(|r| present[r]) {
+      return Some(high as u8)
+    }
+  }
+  return None
+}
+
+fn ranks_present(cards: &Vec<Card>) -> [bool; 15] {
+  let mut present = [false; 15];
+  for c in cards {
+    set_rank(&mut present, c.rank);
+  }
+  return present
+}
+
+// hero makes a straight with given extra ranks, and it is better than whatever board and extra ranks make alone
+fn hero_straight_with(hero_present: &[bool; 15], board_present: &[bool; 15], extra: &[u8]) -> bool {
+  let mut with_hero = *hero_present;
+  let mut board_only = *board_present;
+  for r in extra {
+    set_rank(&mut with_hero, *r);
+    set_rank(&mut board_only, *r);
+  }
+  return match (straight_high(&with_hero), straight_high(&board_only)) {
+    (Some(h), Some(b)) => h > b,
+    (Some(_), None) => true,
+    _ => false,
+  }
+}
+
+// 4 consecutive ranks that use a hole card and can be completed from both ends
+fn has_open_ender(hero_present: &[bool; 15], board_present: &[bool; 15]) -> bool {
+  for low in 2..=10usize {
+    let needed_low = low-1;
+    let needed_high = low+4;
+    if needed_high > 14 || !(low..low+4).all(|r| hero_present[r]) {
+      continue;
+    }
+    if (low..low+4).all(|r| board_present[r]) {
+      continue;
+    }
+    if !hero_present[needed_low] && !hero_present[needed_high] {
+      return true
+    }
+  }
+  return false
+}
+
+// scores every opponent combo from the deck left after the card lands against what hero makes with it.
+// remaining_deck has hero's cards and the board taken out already
+fn dirty_reason(hand: &[Card], board: &[Card], card: Card, remaining_deck: &[Card], score_table: &ScoreTable) -> Option<DirtyReason> {
+  let mut board_cards: Vec<u8> = board.iter().map(card_index).collect();
+  board_cards.push(card_index(&card));
+  let mut hero_cards: Vec<u8> = hand.iter().map(card_index).collect();
+  hero_cards.extend(board_cards.iter());
+  let hero_score = score_table.best_score(&hero_cards);
+
+  let deck: Vec<u8> = remaining_deck.iter().filter(|c| **c != card).map(card_index).collect();
+  let mut villain_cards = vec![0, 0];
+  villain_cards.extend(board_cards.iter());
+  let mut beaten_by = 0;
+  let mut total = 0;
+  let mut best = hero_score;
+  for i in 0..deck.len() {
+    for j in (i+1)..deck.len() {
+      villain_cards[0] = deck[i];
+      villain_cards[1] = deck[j];
+      let score = score_table.best_score(&villain_cards);
+      if score > hero_score {
+        beaten_by += 1;
+        if score > best {
+          best = score;
+        }
+      }
+      total += 1;
+    }
+  }
+  if beaten_by == 0 {
+    return None
+  }
+  return Some(DirtyReason{beaten_by: beaten_by, total: total, best: get_best_hand_string(best)})
+}
+
+// classifies hero's draws on flop or turn and lists every card from remaining deck that completes one of them
+pub fn analyze_draws(hand: &[Card], board: &Vec<Card>, card_deck: &[Card], score_table: &ScoreTable) -> DrawAnalysis {
+  let mut all_cards = hand.to_vec();
+  all_cards.extend(board.iter());
+  let mut remaining_deck = card_deck.to_vec();
+  remaining_deck.retain(|x| !all_cards.contains(x));
+
+  let mut draws = Vec::new();
+  let mut out_draws: Vec<(Card, DrawType)> = Vec::new();
+
+  // flush draws: 4 of a suit using hole cards, or 3 of a suit on the flop for a backdoor
+  let mut has_backdoor_flush = false;
+  for suit in hand.iter().map(|c| c.suit).unique() {
+    let num_suited = all_cards.iter().filter(|c| c.suit == suit).count();
+    if num_suited == 4 {
+      // nut flush draw is when hero holds highest card of the suit that is not on the board
+      let mut nut_rank = 14;
+      while board.iter().any(|c| c.suit == suit && c.rank == nut_rank) {
+        nut_rank -= 1;
+      }
+      let has_nut = hand.iter().any(|c| c.suit == suit && c.rank == nut_rank);
+      let draw = if has_nut { DrawType::NutFlushDraw } else { DrawType::FlushDraw };
+      draws.push(draw);
+      for c in &remaining_deck {
+        if c.suit == suit {
+          out_draws.push((*c, draw));
+        }
+      }
+    } else if num_suited == 3 && board.len() == 3 {
+      has_backdoor_flush = true;
+    }
+  }
+  if has_backdoor_flush && !draws.iter().any(|d| d.is_flush_draw()) {
+    draws.push(DrawType::BackdoorFlushDraw);
+  }
+
+  // straight draws
+  let hero_present = ranks_present(&all_cards);
+  let board_present = ranks_present(board);
+  if straight_high(&hero_present).is_none() {
+    let mut completing_ranks = Vec::new();
+    for r in 2..=14u8 {
+      if hero_straight_with(&hero_present, &board_present, &[r]) {
+        completing_ranks.push(r);
+      }
+    }
+    let straight_draw = if completing_ranks.len() >= 2 && has_open_ender(&hero_present, &board_present) {
+      Some(DrawType::OpenEndedStraightDraw)
+    } else if completing_ranks.len() >= 2 {
+      Some(DrawType::DoubleGutshot)
+    } else if completing_ranks.len() == 1 {
+      Some(DrawType::Gutshot)
+    } else {
+      None
+    };
+    if let Some(draw) = straight_draw {
+      draws.push(draw);
+      for c in &remaining_deck {
+        if completing_ranks.contains(&c.rank) {
+          out_draws.push((*c, draw));
+        }
+      }
+    } else if board.len() == 3 {
+      let mut has_backdoor = false;
+      for r1 in 2..=14u8 {
+        for r2 in (r1+1)..=14u8 {
+          if hero_straight_with(&hero_present, &board_present, &[r1, r2]) {
+            has_backdoor = true;
+          }
+        }
+      }
+      if has_backdoor {
+        draws.push(DrawType::BackdoorStraightDraw);
+      }
+    }
+  }
+
+  // overcards only matter when hero has nothing paired yet
+  let max_board_rank = board.iter().map(|c| c.rank).max().unwrap_or(0);
+  let hero_paired = hand[0].rank == hand[1].rank || hand.iter().any(|h| board.iter().any(|c| c.rank == h.rank));
+  if !hero_paired {
+    let overcards: Vec<u8> = hand.iter().filter(|h| h.rank > max_board_rank).map(|h| h.rank).collect();
+    if !overcards.is_empty() {
+      draws.push(DrawType::Overcards);
+      for c in &remaining_deck {
+        if overcards.contains(&c.rank) {
+          out_draws.push((*c, DrawType::Overcards));
+        }
+      }
+    }
+  }
+
+  let mut outs: Vec<Out> = Vec::new();
+  for (card, draw) in out_draws {
+    match outs.iter_mut().find(|o| o.card == card) {
+      Some(o) => o.completes.push(draw),
+      None => outs.push(Out{card: card, completes: vec![draw], dirty: None}),
+    }
+  }
+  if board.len() < 5 {
+    for o in outs.iter_mut() {
+      o.dirty = dirty_reason(hand, board, o.card, &remaining_deck, score_table);
+    }
+  }
+  outs.sort_by_key(|o| o.card);
+
+  return DrawAnalysis{draws: draws, outs: outs}
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::{NUM_CARDS, index_to_card};
+  use crate::score_table::tests::holdem_score_table;
+
+  #[test]
+  fn outs_beaten_by_a_combo_are_dirty() {
+    let hand = conv_string_to_cards("Ah Kh");
+    let board = conv_string_to_cards("Qh 7h 2c");
+    let deck: Vec<Card> = (0..NUM_CARDS as u8).map(index_to_card).collect();
+    let analysis = analyze_draws(&hand, &board, &deck, holdem_score_table());
+    assert!(analysis.draws.contains(&DrawType::NutFlushDraw));
+    let out = |s: &str| analysis.outs.iter().find(|o| o.card == conv_string_to_cards(s)[0]).unwrap();
+    // nothing beats the nut flush on an unpaired board
+    assert!(out("3h").dirty.is_none());
+    // pairing the board gives full houses and quads
+    let dirty = out("2h").dirty.unwrap();
+    assert!(dirty.best == HandRank::FourOfAKind, "{}", dirty);
+    assert!(dirty.beaten_by > 0 && dirty.beaten_by < dirty.total);
+    // an ace for top pair loses to sets, two pairs and flushes
+    assert!(out("As").dirty.is_some());
+  }
+}
@@ -0,0 +1,517 @@
+use std::fmt;
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use crate::Card;
+use crate::hand_history::Street;
+use crate::score_table::{ScoreTable, card_index};
+
+// chips are whole units so pots can be split with odd chip rules
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum PlayerAction {
+  Fold,
+  Check,
+  Call,
+  // Bet and Raise both carry the total bet on the street after the action
+  Bet(u32),
+  Raise(u32),
+  AllIn,
+}
+
+impl fmt::Display for PlayerAction {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PlayerAction::Fold => write!(f, "fold"),
+      PlayerAction::Check => write!(f, "check"),
+      PlayerAction::Call => write!(f, "call"),
+      PlayerAction::Bet(amount) => write!(f, "bet {}", amount),
+      PlayerAction::Raise(to) => write!(f, "raise to {}", to),
+      PlayerAction::AllIn => write!(f, "all-in"),
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+pub struct GameConfig {
+  pub small_blind: u32,
+  pub big_blind: u32,
+  pub ante: u32,
+}
+
+// everything a seat can see when it has to act
+pub struct GameView<'a> {
+  pub seat: usize,
+  pub button: usize,
+  pub num_seats: usize,
+  pub config: GameConfig,
+  pub street: Street,
+  pub hole_cards: &'a Vec<Card>,
+  pub board: &'a Vec<Card>,
+  pub stacks: &'a Vec<u32>,
+  // chips put in on this street and in the whole hand, per seat
+  pub street_bets: &'a Vec<u32>,
+  pub total_bets: &'a Vec<u32>,
+  pub folded: &'a Vec<bool>,
+  pub pot: u32,
+  pub to_call: u32,
+  // smallest and largest legal total bet on the street, 0 if player can't bet or raise
+  pub min_raise_to: u32,
+  pub max_raise_to: u32,
+  pub history: &'a Vec<(usize, Street, PlayerAction)>,
+}
+
+impl GameView<'_> {
+  pub fn can_raise(&self) -> bool {
+    return self.max_raise_to > 0
+  }
+
+  pub fn num_active_opponents(&self) -> usize {
+    return (0..self.num_seats).filter(|i| *i != self.seat && !self.folded[*i] && (self.stacks[*i] > 0 || self.total_bets[*i] > 0)).count()
+  }
+}
+
+// anything that can sit at the table: bots, a human at the console, a solver's strategy
+pub trait Agent {
+  fn name(&self) -> String;
+  fn act(&mut self, view: &GameView) -> PlayerAction;
+}
+
+pub struct Pot {
+  pub amount: u32,
+  pub eligible: Vec<usize>,
+  pub winners: Vec<usize>,
+}
+
+pub struct HandResult {
+  pub button: usize,
+  pub hole_cards: Vec<Vec<Card>>,
+  pub board: Vec<Card>,
+  pub history: Vec<(usize, Street, PlayerAction)>,
+  // main pot first, then side pots
+  pub pots: Vec<Pot>,
+  // seats in the order they show their cards, empty when everyone else folded
+  pub showdown_order: Vec<usize>,
+  // chips won minus chips put in, per seat
+  pub net: Vec<i64>,
+}
+
+struct HandState {
+  stacks: Vec<u32>,
+  hole_cards: Vec<Vec<Card>>,
+  board: Vec<Card>,
+  street: Street,
+  street_bets: Vec<u32>,
+  total_bets: Vec<u32>,
+  folded: Vec<bool>,
+  // seat was dealt into the hand
+  in_hand: Vec<bool>,
+  history: Vec<(usize, Street, PlayerAction)>,
+  current_bet: u32,
+  // size of the last full bet or raise, next raise has to be at least this much more
+  last_raise_size: u32,
+  last_aggressor: Option<usize>,
+}
+
+impl HandState {
+  fn pot(&self) -> u32 {
+    return self.total_bets.iter().sum()
+  }
+
+  fn is_live(&self, seat: usize) -> bool {
+    return self.in_hand[seat] && !self.folded[seat]
+  }
+
+  fn can_act(&self, seat: usize) -> bool {
+    return self.is_live(seat) && self.stacks[seat] > 0
+  }
+
+  fn num_live(&self) -> usize {
+    return (0..self.stacks.len()).filter(|i| self.is_live(*i)).count()
+  }
+
+  fn put_in(&mut self, seat: usize, amount: u32) -> u32 {
+    let amount = amount.min(self.stacks[seat]);
+    self.stacks[seat] -= amount;
+    self.street_bets[seat] += amount;
+    self.total_bets[seat] += amount;
+    return amount
+  }
+}
+
+// next seat after `from` that passes the filter, going clockwise
+fn next_seat(from: usize, num_seats: usize, filter: impl Fn(usize) -> bool) -> usize {
+  for i in 1..=num_seats {
+    let seat = (from+i) % num_seats;
+    if filter(seat) {
+      return seat
+    }
+  }
+  return from
+}
+
+pub struct Table {
+  pub config: GameConfig,
+  pub stacks: Vec<u32>,
+  pub button: usize,
+  card_deck: Vec<Card>,
+}
+
+impl Table {
+  pub fn new(config: GameConfig, stacks: Vec<u32>, card_deck: &[Card]) -> Table {
+    return Table{config: config, stacks: stacks, button: 0, card_deck: card_deck.to_vec()}
+  }
+
+  pub fn num_players_with_chips(&self) -> usize {
+    return self.stacks.iter().filter(|s| **s > 0).count()
+  }
+
+  // plays one hand between every seat with chips, moves the button after it
+  pub fn play_hand(&mut self, agents: &mut Vec<Box<dyn Agent + '_>>, score_table: &ScoreTable, rng: &mut ChaCha20Rng) -> HandResult {
+    let num_seats = self.stacks.len();
+    assert!(agents.len() == num_seats, "need one agent per seat");
+    assert!(self.num_players_with_chips() >= 2, "need at least 2 players with chips");
+    if self.stacks[self.button] == 0 {
+      let stacks = self.stacks.clone();
+      self.button = next_seat(self.button, num_seats, |s| stacks[s] > 0);
+    }
+
+    let mut deck = self.card_deck.clone();
+    for i in (1..deck.len()).rev() {
+      let j = rng.gen_range(0..=i);
+      deck.swap(i, j);
+    }
+
+    let in_hand: Vec<bool> = self.stacks.iter().map(|s| *s > 0).collect();
+    let mut state = HandState {
+      stacks: self.stacks.clone(),
+      hole_cards: vec![Vec::new(); num_seats],
+      board: Vec::new(),
+      street: Street::Preflop,
+      street_bets: vec![0; num_seats],
+      total_bets: vec![0; num_seats],
+      folded: vec![false; num_seats],
+      in_hand: in_hand.clone(),
+      history: Vec::new(),
+      current_bet: 0,
+      last_raise_size: self.config.big_blind,
+      last_aggressor: None,
+    };
+    for _ in 0..2 {
+      for seat in (0..num_seats).filter(|s| in_hand[*s]) {
+        state.hole_cards[seat].push(deck.pop().unwrap());
+      }
+    }
+
+    // antes are dead money, they don't count towards the bet on the street
+    if self.config.ante > 0 {
+      for seat in (0..num_seats).filter(|s| in_hand[*s]) {
+        state.put_in(seat, self.config.ante);
+      }
+      state.street_bets = vec![0; num_seats];
+    }
+
+    // heads up the button posts the small blind and acts first preflop
+    let num_in_hand = in_hand.iter().filter(|s| **s).count();
+    let sb_seat = if num_in_hand == 2 { self.button } else { next_seat(self.button, num_seats, |s| in_hand[s]) };
+    let bb_seat = next_seat(sb_seat, num_seats, |s| in_hand[s]);
+    state.put_in(sb_seat, self.config.small_blind);
+    state.put_in(bb_seat, self.config.big_blind);
+    state.current_bet = self.config.big_blind;
+
+    let first_preflop = next_seat(bb_seat, num_seats, |s| in_hand[s]);
+    self.betting_round(&mut state, first_preflop, agents);
+
+    for street in [Street::Flop, Street::Turn, Street::River] {
+      if state.num_live() <= 1 {
+        break;
+      }
+      let num_cards = if street == Street::Flop { 3 } else { 1 };
+      for _ in 0..num_cards {
+        state.board.push(deck.pop().unwrap());
+      }
+      state.street = street;
+      state.street_bets = vec![0; num_seats];
+      state.current_bet = 0;
+      state.last_raise_size = self.config.big_blind;
+      state.last_aggressor = None;
+      // no betting when at most one player still has chips behind
+      let num_can_act = (0..num_seats).filter(|s| state.can_act(*s)).count();
+      if num_can_act >= 2 {
+        let first = next_seat(self.button, num_seats, |s| state.can_act(s));
+        self.betting_round(&mut state, first, agents);
+      }
+    }
+
+    let result = self.distribute_pots(&state, score_table);
+    for seat in 0..num_seats {
+      self.stacks[seat] = (self.stacks[seat] as i64 + result.net[seat]) as u32;
+    }
+    let stacks = self.stacks.clone();
+    self.button = next_seat(self.button, num_seats, |s| stacks[s] > 0);
+    return result
+  }
+
+  // turns whatever agent asked for into a legal action: checking into a bet folds, raising more than the stack goes all-in,
+  // raises below the minimum become a minimum raise, raising when action wasn't reopened becomes a call
+  fn legalize(&self, state: &HandState, seat: usize, action: PlayerAction, can_reraise: bool) -> PlayerAction {
+    let to_call = state.current_bet - state.street_bets[seat];
+    let max_to = state.street_bets[seat] + state.stacks[seat];
+    let min_to = (state.current_bet + state.last_raise_size).max(self.config.big_blind);
+    let call = || -> PlayerAction {
+      if to_call == 0 {
+        return PlayerAction::Check
+      }
+      return if to_call >= state.stacks[seat] { PlayerAction::AllIn } else { PlayerAction::Call }
+    };
+    let raise_to = |to: u32| -> PlayerAction {
+      if !can_reraise || max_to <= state.current_bet {
+        return call()
+      }
+      if to >= max_to {
+        return PlayerAction::AllIn
+      }
+      let to = to.max(min_to);
+      if to >= max_to {
+        return PlayerAction::AllIn
+      }
+      return if state.current_bet == 0 { PlayerAction::Bet(to) } else { PlayerAction::Raise(to) }
+    };
+    return match action {
+      PlayerAction::Fold => if to_call == 0 { PlayerAction::Check } else { PlayerAction::Fold },
+      PlayerAction::Check => if to_call == 0 { PlayerAction::Check } else { PlayerAction::Fold },
+      PlayerAction::Call => call(),
+      PlayerAction::Bet(to) | PlayerAction::Raise(to) => raise_to(to),
+      // going all-in for more than a call is a raise, which may not be allowed
+      PlayerAction::AllIn => if !can_reraise && max_to > state.current_bet { call() } else { PlayerAction::AllIn },
+    }
+  }
+
+  fn betting_round(&self, state: &mut HandState, first: usize, agents: &mut [Box<dyn Agent + '_>]) {
+    let num_seats = state.stacks.len();
+    let mut needs_action: Vec<bool> = (0..num_seats).map(|s| state.can_act(s)).collect();
+    // a player that already acted can only re-raise after a full raise
+    let mut can_reraise = vec![true; num_seats];
+    let mut seat = first;
+    loop {
+      if state.num_live() <= 1 {
+        return
+      }
+      if !needs_action.iter().any(|n| *n) {
+        return
+      }
+      if !needs_action[seat] || !state.can_act(seat) {
+        needs_action[seat] = false;
+        seat = (seat+1) % num_seats;
+        continue;
+      }
+      // only one player left with chips and nothing to call
+      let others_can_act = (0..num_seats).any(|s| s != seat && state.can_act(s));
+      let to_call = state.current_bet - state.street_bets[seat];
+      if !others_can_act && to_call == 0 {
+        return
+      }
+
+      let max_to = state.street_bets[seat] + state.stacks[seat];
+      let min_to = (state.current_bet + state.last_raise_size).max(self.config.big_blind).min(max_to);
+      let raise_allowed = can_reraise[seat] && others_can_act && max_to > state.current_bet;
+      let requested = {
+        let view = GameView {
+          seat: seat,
+          button: self.button,
+          num_seats: num_seats,
+          config: self.config,
+          street: state.street,
+          hole_cards: &state.hole_cards[seat],
+          board: &state.board,
+          stacks: &state.stacks,
+          street_bets: &state.street_bets,
+          total_bets: &state.total_bets,
+          folded: &state.folded,
+          pot: state.pot(),
+          to_call: to_call.min(state.stacks[seat]),
+          min_raise_to: if raise_allowed { min_to } else { 0 },
+          max_raise_to: if raise_allowed { max_to } else { 0 },
+          history: &state.history,
+        };
+        agents[seat].act(&view)
+      };
+      let action = self.legalize(state, seat, requested, raise_allowed);
+      needs_action[seat] = false;
+      can_reraise[seat] = false;
+
+      let previous_bet = state.current_bet;
+      match action {
+        PlayerAction::Fold => state.folded[seat] = true,
+        PlayerAction::Check => {},
+        PlayerAction::Call => {
+          state.put_in(seat, to_call);
+        },
+        PlayerAction::Bet(to) | PlayerAction::Raise(to) => {
+          state.put_in(seat, to - state.street_bets[seat]);
+        },
+        PlayerAction::AllIn => {
+          let stack = state.stacks[seat];
+          state.put_in(seat, stack);
+        },
+      }
+      state.history.push((seat, state.street, action));
+
+      if state.street_bets[seat] > previous_bet {
+        let raise_size = state.street_bets[seat] - previous_bet;
+        state.current_bet = state.street_bets[seat];
+        state.last_aggressor = Some(seat);
+        let full_raise = raise_size >= state.last_raise_size;
+        if full_raise {
+          state.last_raise_size = raise_size;
+        }
+        for other in 0..num_seats {
+          if other != seat && state.can_act(other) {
+            needs_action[other] = true;
+            if full_raise {
+              can_reraise[other] = true;
+            }
+          }
+        }
+      }
+      seat = (seat+1) % num_seats;
+    }
+  }
+
+  fn distribute_pots(&self, state: &HandState, score_table: &ScoreTable) -> HandResult {
+    let num_seats = state.stacks.len();
+    let live: Vec<usize> = (0..num_seats).filter(|s| state.is_live(*s)).collect();
+    let showdown = live.len() > 1;
+
+    let mut scores = vec![0.0f32; num_seats];
+    if showdown {
+      for seat in &live {
+        let mut cards: Vec<u8> = state.hole_cards[*seat].iter().map(card_index).collect();
+        cards.extend(state.board.iter().map(card_index));
+        scores[*seat] = score_table.best_score(&cards);
+      }
+    }
+
+    // a layer for every distinct contribution level, players that put in at least that much are in it
+    let mut levels: Vec<u32> = state.total_bets.iter().cloned().filter(|b| *b > 0).collect();
+    levels.sort();
+    levels.dedup();
+    let mut pots: Vec<Pot> = Vec::new();
+    let mut carry = 0;
+    let mut prev_level = 0;
+    for level in levels {
+      let amount: u32 = state.total_bets.iter().map(|b| (*b).min(level) - (*b).min(prev_level)).sum::<u32>() + carry;
+      prev_level = level;
+      let eligible: Vec<usize> = live.iter().cloned().filter(|s| state.total_bets[*s] >= level).collect();
+      if eligible.is_empty() {
+        // only folded players got this deep, chips go to the pot below
+        match pots.last_mut() {
+          Some(p) => p.amount += amount,
+          None => carry = amount,
+        }
+        continue;
+      }
+      carry = 0;
+      // same players as the previous layer means it's the same pot
+      match pots.last_mut() {
+        Some(p) if p.eligible == eligible => p.amount += amount,
+        _ => pots.push(Pot{amount: amount, eligible: eligible, winners: Vec::new()}),
+      }
+    }
+
+    let mut won = vec![0u32; num_seats];
+    for pot in pots.iter_mut() {
+      let best = pot.eligible.iter().map(|s| scores[*s]).fold(f32::MIN, f32::max);
+      // odd chips go to the winners closest to the left of the button
+      let mut winners: Vec<usize> = pot.eligible.iter().cloned().filter(|s| scores[*s] == best).collect();
+      winners.sort_by_key(|s| (s + num_seats - self.button - 1) % num_seats);
+      let share = pot.amount / winners.len() as u32;
+      let odd_chips = pot.amount % winners.len() as u32;
+      for (i, w) in winners.iter().enumerate() {
+        won[*w] += share + if (i as u32) < odd_chips { 1 } else { 0 };
+      }
+      pot.winners = winners;
+    }
+
+    // last aggressor on the river shows first, otherwise first player left of the button
+    let mut showdown_order = Vec::new();
+    if showdown {
+      let first = match state.last_aggressor {
+        Some(s) if state.street == Street::River && state.is_live(s) => s,
+        _ => next_seat(self.button, num_seats, |s| state.is_live(s)),
+      };
+      for i in 0..num_seats {
+        let seat = (first+i) % num_seats;
+        if state.is_live(seat) {
+          showdown_order.push(seat);
+        }
+      }
+    }
+
+    let net = (0..num_seats).map(|s| won[s] as i64 - state.total_bets[s] as i64).collect();
+    return HandResult {
+      button: self.button,
+      hole_cards: state.hole_cards.clone(),
+      board: state.board.clone(),
+      history: state.history.clone(),
+      pots: pots,
+      showdown_order: showdown_order,
+      net: net,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::tests::holdem_score_table;
+
+  // river reached with everyone's chips in, board and hole cards make exactly five cards per player
+  fn showdown_state(hole_cards: &[&str], board: &str, total_bets: Vec<u32>, folded: Vec<bool>) -> HandState {
+    let n = hole_cards.len();
+    return HandState{
+      stacks: vec![0; n],
+      hole_cards: hole_cards.iter().map(|h| conv_string_to_cards(h)).collect(),
+      board: conv_string_to_cards(board),
+      street: Street::River,
+      street_bets: vec![0; n],
+      total_bets: total_bets,
+      folded: folded,
+      in_hand: vec![true; n],
+      history: Vec::new(),
+      current_bet: 0,
+      last_raise_size: 0,
+      last_aggressor: None,
+    }
+  }
+
+  #[test]
+  fn side_pots() {
+    let score_table = holdem_score_table();
+    let config = GameConfig{small_blind: 1, big_blind: 2, ante: 0};
+    let table = Table::new(config, vec![0; 3], &[]);
+    // short all-in has the straight, seat 1 trips, seat 2 nothing
+    let state = showdown_state(&["9s Ts", "6s 6d", "Ah Kc"], "6h 7d 8c", vec![100, 300, 300], vec![false; 3]);
+    let result = table.distribute_pots(&state, score_table);
+    assert_eq!(result.pots.len(), 2);
+    assert_eq!((result.pots[0].amount, result.pots[0].winners.clone()), (300, vec![0]));
+    assert_eq!((result.pots[1].amount, result.pots[1].winners.clone()), (400, vec![1]));
+    assert_eq!(result.pots[1].eligible, vec![1, 2]);
+    assert_eq!(result.net, vec![200, 100, -300]);
+  }
+
+  #[test]
+  fn odd_chip_goes_left_of_button() {
+    let score_table = holdem_score_table();
+    let config = GameConfig{small_blind: 1, big_blind: 2, ante: 0};
+    let mut table = Table::new(config, vec![0; 3], &[]);
+    // both live players make the same straight, folded seat 2 left one chip in
+    let state = showdown_state(&["Js Th", "Jd Tc", "6s 7s"], "Ah Kd Qc", vec![10, 10, 1], vec![false, false, true]);
+    let result = table.distribute_pots(&state, score_table);
+    assert_eq!(result.pots.len(), 1);
+    assert_eq!(result.pots[0].amount, 21);
+    assert_eq!(result.net, vec![0, 1, -1]);
+    table.button = 1;
+    let result = table.distribute_pots(&state, score_table);
+    assert_eq!(result.net, vec![1, 0, -1]);
+  }
+}
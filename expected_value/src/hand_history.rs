@@ -0,0 +1,823 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::{Card, TablePosition};
+
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum Site {
+  PokerStars,
+  // Ignition, Bovada and Bodog share the same format
+  Ignition,
+}
+
+impl fmt::Display for Site {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Site::PokerStars => write!(f, "PokerStars"),
+      Site::Ignition => write!(f, "Ignition"),
+    }
+  }
+}
+
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum Street {
+  Preflop = 0,
+  Flop = 1,
+  Turn = 2,
+  River = 3,
+}
+
+impl fmt::Display for Street {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Street::Preflop => write!(f, "PREFLOP"),
+      Street::Flop => write!(f, "FLOP"),
+      Street::Turn => write!(f, "TURN"),
+      Street::River => write!(f, "RIVER"),
+    }
+  }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ActionKind {
+  Ante,
+  SmallBlind,
+  BigBlind,
+  Fold,
+  Check,
+  Call,
+  Bet,
+  Raise,
+  // uncalled part of a bet that went back to the player
+  Return,
+}
+
+impl fmt::Display for ActionKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ActionKind::Ante => write!(f, "ante"),
+      ActionKind::SmallBlind => write!(f, "small blind"),
+      ActionKind::BigBlind => write!(f, "big blind"),
+      ActionKind::Fold => write!(f, "fold"),
+      ActionKind::Check => write!(f, "check"),
+      ActionKind::Call => write!(f, "call"),
+      ActionKind::Bet => write!(f, "bet"),
+      ActionKind::Raise => write!(f, "raise"),
+      ActionKind::Return => write!(f, "return"),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Action {
+  pub player: String,
+  pub street: Street,
+  pub kind: ActionKind,
+  // chips put into the pot by this action (or taken back for Return)
+  pub amount: f32,
+  // player's total bet on this street after the action
+  pub to: f32,
+  pub all_in: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Seat {
+  pub seat: u8,
+  pub player: String,
+  pub stack: f32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HandHistory {
+  pub site: Site,
+  pub hand_id: String,
+  pub table: String,
+  pub date: String,
+  pub small_blind: f32,
+  pub big_blind: f32,
+  pub ante: f32,
+  pub button_seat: u8,
+  pub max_players: usize,
+  pub seats: Vec<Seat>,
+  pub hero: Option<String>,
+  // hero's cards, plus anything shown or dealt face up in the history
+  pub hole_cards: HashMap<String, Vec<Card>>,
+  pub board: Vec<Card>,
+  pub actions: Vec<Action>,
+  // players that showed their hand at showdown
+  pub showdown: Vec<String>,
+  pub collected: Vec<(String, f32)>,
+  pub total_pot: f32,
+  pub rake: f32,
+}
+
+impl HandHistory {
+  fn new(site: Site, hand_id: String) -> HandHistory {
+    return HandHistory {
+      site: site,
+      hand_id: hand_id,
+      table: String::new(),
+      date: String::new(),
+      small_blind: 0.0,
+      big_blind: 0.0,
+      ante: 0.0,
+      button_seat: 0,
+      max_players: 0,
+      seats: Vec::new(),
+      hero: None,
+      hole_cards: HashMap::new(),
+      board: Vec::new(),
+      actions: Vec::new(),
+      showdown: Vec::new(),
+      collected: Vec::new(),
+      total_pot: 0.0,
+      rake: 0.0,
+    }
+  }
+
+  pub fn hero_cards(&self) -> Option<&Vec<Card>> {
+    return match &self.hero {
+      Some(hero) => self.hole_cards.get(hero),
+      None => None,
+    }
+  }
+
+  // board as it was seen on a given street
+  pub fn board_on(&self, street: Street) -> Vec<Card> {
+    let n = match street {
+      Street::Preflop => 0,
+      Street::Flop => 3,
+      Street::Turn => 4,
+      Street::River => 5,
+    };
+    return self.board.iter().take(n).cloned().collect()
+  }
+
+  pub fn street_actions(&self, street: Street) -> Vec<&Action> {
+    return self.actions.iter().filter(|a| a.street == street).collect()
+  }
+
+  // chips won minus chips put in, for a given player
+  pub fn net_result(&self, player: &str) -> f32 {
+    let mut net = 0.0;
+    for a in &self.actions {
+      if a.player != player {
+        continue;
+      }
+      if a.kind == ActionKind::Return {
+        net += a.amount;
+      } else {
+        net -= a.amount;
+      }
+    }
+    for (p, amount) in &self.collected {
+      if p == player {
+        net += amount;
+      }
+    }
+    return net
+  }
+
+  // players in seat order, starting with the first seat after the button
+  pub fn players_from_button(&self) -> Vec<String> {
+    let mut seats = self.seats.clone();
+    seats.sort_by_key(|s| s.seat);
+    let button_idx = seats.iter().position(|s| s.seat == self.button_seat).unwrap_or(seats.len()-1);
+    let mut res = Vec::new();
+    for i in 1..=seats.len() {
+      res.push(seats[(button_idx+i) % seats.len()].player.clone());
+    }
+    return res
+  }
+
+  // preflop position of a player, None for the big blind that never opens a pot
+  pub fn position_of(&self, player: &str) -> Option<TablePosition> {
+    let players = self.players_from_button();
+    let i = players.iter().position(|p| p == player)?;
+    return position_from_button(i, players.len())
+  }
+}
+
+// position of the i-th player after the button (0 is small blind, n-1 is the button) at a table of n players.
+// uses the same buckets as the position ranges: for 9 players UTG - Early, 4,5,6 - Mid, 7,8 - Late
+pub fn position_from_button(i: usize, n: usize) -> Option<TablePosition> {
+  if i == n-1 {
+    // heads up button posts small blind
+    return Some(if n == 2 { TablePosition::SB } else { TablePosition::Button })
+  }
+  if i == 0 {
+    return if n == 2 { None } else { Some(TablePosition::SB) }
+  }
+  if i == 1 {
+    return None
+  }
+  if n > 6 {
+    return Some(if i >= n-3 { TablePosition::Late } else if i >= n-6 { TablePosition::Mid } else { TablePosition::Early })
+  }
+  return Some(if i == n-2 { TablePosition::Late } else if i == n-3 { TablePosition::Mid } else { TablePosition::Early })
+}
+
+fn invalid_data(msg: String) -> Error {
+  return Error::new(ErrorKind::InvalidData, msg)
+}
+
+// "$1,234.56" or "1234" -> 1234.56
+fn parse_amount(s: &str) -> Option<f32> {
+  let cleaned: String = s.trim().trim_start_matches('(').trim_end_matches(')').chars().filter(|c| *c != '$' && *c != ',').collect();
+  let cleaned = cleaned.trim_end_matches(|c: char| c.is_alphabetic()).trim();
+  return lexical::parse(cleaned).ok()
+}
+
+// first amount in a string, e.g. "calls $0.04 and is all-in" -> 0.04
+fn first_amount(s: &str) -> Option<f32> {
+  for word in s.split_whitespace() {
+    if word.chars().any(|c| c.is_ascii_digit()) {
+      if let Some(v) = parse_amount(word) {
+        return Some(v)
+      }
+    }
+  }
+  return None
+}
+
+// amount after " to ", e.g. "raises $0.04 to $0.06" -> 0.06
+fn to_amount(s: &str) -> Option<f32> {
+  return match s.find(" to ") {
+    Some(idx) => first_amount(&s[idx+4..]),
+    None => None,
+  }
+}
+
+fn parse_card(s: &str) -> Option<Card> {
+  let s = s.trim();
+  if s.len() < 2 || s.len() > 3 {
+    return None
+  }
+  let (rank_str, suit_str) = s.split_at(s.len()-1);
+  let valid_rank = matches!(rank_str, "2"|"3"|"4"|"5"|"6"|"7"|"8"|"9"|"T"|"10"|"J"|"Q"|"K"|"A");
+  let valid_suit = matches!(suit_str, "h"|"s"|"c"|"d"|"H"|"S"|"C"|"D");
+  if !valid_rank || !valid_suit {
+    return None
+  }
+  return s.parse::<Card>().ok()
+}
+
+// every card in all [..] groups of a line, e.g. "*** TURN *** [7s 8c 2s] [Kc]" -> 7s 8c 2s Kc
+fn parse_bracket_cards(s: &str) -> Vec<Card> {
+  let mut res = Vec::new();
+  let mut rest = s;
+  while let Some(start) = rest.find('[') {
+    let end = match rest[start..].find(']') {
+      Some(e) => start+e,
+      None => break,
+    };
+    for part in rest[start+1..end].split_whitespace() {
+      if let Some(card) = parse_card(part) {
+        res.push(card);
+      }
+    }
+    rest = &rest[end+1..];
+  }
+  return res
+}
+
+fn detect_site(header: &str) -> Option<Site> {
+  if header.starts_with("PokerStars ") {
+    return Some(Site::PokerStars)
+  }
+  if header.starts_with("Ignition Hand #") || header.starts_with("Bovada Hand #") || header.starts_with("Bodog Hand #") {
+    return Some(Site::Ignition)
+  }
+  return None
+}
+
+// Ignition marks hero with [ME] and pads names with spaces
+fn clean_player_name(name: &str) -> (String, bool) {
+  let is_hero = name.contains("[ME]");
+  return (name.replace("[ME]", "").trim().to_string(), is_hero)
+}
+
+// splits "player: action" (PokerStars) or "player : action" (Ignition)
+fn split_player_line(site: Site, line: &str) -> Option<(&str, &str)> {
+  let sep = match site {
+    Site::PokerStars => ": ",
+    Site::Ignition => " : ",
+  };
+  let idx = line.find(sep)?;
+  return Some((&line[..idx], line[idx+sep.len()..].trim()))
+}
+
+struct StreetState {
+  street: Street,
+  committed: HashMap<String, f32>,
+  max_bet: f32,
+}
+
+impl StreetState {
+  fn next_street(&mut self, street: Street) {
+    self.street = street;
+    self.committed.clear();
+    self.max_bet = 0.0;
+  }
+}
+
+fn parse_header(hh: &mut HandHistory, header: &str) {
+  match hh.site {
+    Site::PokerStars => {
+      // PokerStars Hand #123: Hold'em No Limit ($0.01/$0.02 USD) - 2020/12/20 12:00:00 ET
+      if let Some(open) = header.rfind('(') {
+        if let Some(close) = header[open..].find(')') {
+          let blinds = &header[open+1..open+close];
+          let parts: Vec<&str> = blinds.split('/').collect();
+          if parts.len() == 2 {
+            hh.small_blind = first_amount(parts[0]).unwrap_or(0.0);
+            hh.big_blind = first_amount(parts[1]).unwrap_or(0.0);
+          }
+        }
+      }
+      if let Some(idx) = header.rfind(" - ") {
+        hh.date = header[idx+3..].trim().to_string();
+      }
+    },
+    Site::Ignition => {
+      // Ignition Hand #4071234567 TBL#18374213 HOLDEM No Limit - 2020-12-20 12:00:00
+      if let Some(idx) = header.find("TBL#") {
+        hh.table = header[idx..].split_whitespace().next().unwrap_or("").to_string();
+      }
+      if let Some(idx) = header.rfind(" - ") {
+        hh.date = header[idx+3..].trim().to_string();
+      }
+    },
+  }
+}
+
+fn parse_seat_line(hh: &mut HandHistory, line: &str) {
+  // Seat 2: Hero ($2.10 in chips)
+  // Seat 5: player5 ($3 in chips) is sitting out <- not dealt in, not a seat of this hand
+  if line.ends_with("is sitting out") || line.contains(" out of hand") {
+    return
+  }
+  let rest = &line[5..];
+  let colon = match rest.find(':') {
+    Some(c) => c,
+    None => return,
+  };
+  let seat = match rest[..colon].trim().parse::<u8>() {
+    Ok(s) => s,
+    Err(_) => return,
+  };
+  let after = &rest[colon+1..];
+  let (name_part, stack) = match after.rfind('(') {
+    Some(open) => (&after[..open], first_amount(&after[open..]).unwrap_or(0.0)),
+    None => (after, 0.0),
+  };
+  let (name, is_hero) = clean_player_name(name_part);
+  if is_hero {
+    hh.hero = Some(name.clone());
+  }
+  hh.seats.push(Seat{seat: seat, player: name, stack: stack});
+}
+
+fn push_action(hh: &mut HandHistory, state: &mut StreetState, player: &str, kind: ActionKind, added: f32, all_in: bool) {
+  let committed = state.committed.entry(player.to_string()).or_insert(0.0);
+  if kind == ActionKind::Return {
+    *committed -= added;
+  } else if kind != ActionKind::Ante {
+    *committed += added;
+  }
+  let to = *committed;
+  if to > state.max_bet {
+    state.max_bet = to;
+  }
+  hh.actions.push(Action{player: player.to_string(), street: state.street, kind: kind, amount: added, to: to, all_in: all_in});
+}
+
+// returns false when line doesn't look like a player action
+fn parse_action(hh: &mut HandHistory, state: &mut StreetState, player: &str, action: &str) -> bool {
+  let lower = action.to_lowercase();
+  let all_in = lower.contains("all-in");
+  let committed = *state.committed.get(player).unwrap_or(&0.0);
+
+  if lower.starts_with("folds") || lower.starts_with("fold") {
+    push_action(hh, state, player, ActionKind::Fold, 0.0, false);
+  } else if lower.starts_with("checks") || lower.starts_with("check") {
+    push_action(hh, state, player, ActionKind::Check, 0.0, false);
+  } else if lower.starts_with("posts the ante") || lower.starts_with("posts ante") || lower.starts_with("ante chip") {
+    // PokerStars "posts the ante $0.01", Ignition "Ante chip $0.01"
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    hh.ante = amount;
+    push_action(hh, state, player, ActionKind::Ante, amount, all_in);
+  } else if lower.starts_with("posts small & big blinds") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    push_action(hh, state, player, ActionKind::BigBlind, amount, all_in);
+  } else if lower.starts_with("posts small blind") || lower.starts_with("small blind") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    if hh.small_blind == 0.0 {
+      hh.small_blind = amount;
+    }
+    push_action(hh, state, player, ActionKind::SmallBlind, amount, all_in);
+  } else if lower.starts_with("posts big blind") || lower.starts_with("big blind") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    if hh.big_blind == 0.0 {
+      hh.big_blind = amount;
+    }
+    push_action(hh, state, player, ActionKind::BigBlind, amount, all_in);
+  } else if lower.starts_with("calls") || lower.starts_with("call") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    push_action(hh, state, player, ActionKind::Call, amount, all_in);
+  } else if lower.starts_with("bets") || lower.starts_with("bet") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    push_action(hh, state, player, ActionKind::Bet, amount, all_in);
+  } else if lower.starts_with("raises") || lower.starts_with("all-in(raise)") {
+    let to = to_amount(&lower).or_else(|| first_amount(&lower)).unwrap_or(0.0);
+    push_action(hh, state, player, ActionKind::Raise, to-committed, all_in);
+  } else if lower.starts_with("all-in") {
+    // Ignition doesn't say if all-in is a bet, call or raise
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    let kind = if state.max_bet == 0.0 {
+      ActionKind::Bet
+    } else if committed+amount > state.max_bet {
+      ActionKind::Raise
+    } else {
+      ActionKind::Call
+    };
+    push_action(hh, state, player, kind, amount, true);
+  } else if lower.starts_with("return uncalled portion of bet") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    push_action(hh, state, player, ActionKind::Return, amount, false);
+  } else if lower.starts_with("card dealt to a spot") || lower.starts_with("shows") || lower.starts_with("showdown") {
+    let cards = parse_bracket_cards(action);
+    if !cards.is_empty() {
+      // Ignition showdown line has best 5 cards, hole cards are already known from the deal
+      if !hh.hole_cards.contains_key(player) || lower.starts_with("shows") {
+        hh.hole_cards.insert(player.to_string(), cards);
+      }
+    }
+    if (lower.starts_with("shows") || lower.starts_with("showdown")) && !hh.showdown.iter().any(|p| p == player) {
+      hh.showdown.push(player.to_string());
+    }
+  } else if lower.starts_with("hand result") || lower.starts_with("collected") {
+    let amount = first_amount(&lower).unwrap_or(0.0);
+    hh.collected.push((player.to_string(), amount));
+  } else {
+    return false
+  }
+  return true
+}
+
+pub fn parse_hand_history(text: &str) -> Result<HandHistory, Error> {
+  let mut lines = text.lines().map(|l| l.trim_end()).filter(|l| !l.is_empty());
+  let header = lines.next().ok_or_else(|| invalid_data("empty hand history".to_string()))?;
+  let site = detect_site(header).ok_or_else(|| invalid_data(format!("unknown hand history format: {}", header)))?;
+  let hand_id = header.split('#').nth(1).and_then(|s| s.split(|c: char| c == ':' || c.is_whitespace()).next()).unwrap_or("").to_string();
+  let mut hh = HandHistory::new(site, hand_id);
+  parse_header(&mut hh, header);
+
+  let mut state = StreetState{street: Street::Preflop, committed: HashMap::new(), max_bet: 0.0};
+  let mut in_summary = false;
+  for line in lines {
+    if line.starts_with("*** ") {
+      let upper = line.to_uppercase();
+      if upper.starts_with("*** FLOP") {
+        state.next_street(Street::Flop);
+        hh.board = parse_bracket_cards(line);
+      } else if upper.starts_with("*** TURN") {
+        state.next_street(Street::Turn);
+        hh.board = parse_bracket_cards(line);
+      } else if upper.starts_with("*** RIVER") {
+        state.next_street(Street::River);
+        hh.board = parse_bracket_cards(line);
+      } else if upper.starts_with("*** SUMMARY") {
+        in_summary = true;
+      }
+      continue;
+    }
+
+    if in_summary {
+      let lower = line.to_lowercase();
+      if lower.starts_with("total pot") {
+        // "Total pot $0.52 | Rake $0.02" or "Total Pot($1.55)"
+        let parts: Vec<&str> = lower.split('|').collect();
+        hh.total_pot = first_amount(&parts[0]["total pot".len()..]).unwrap_or(0.0);
+        for p in &parts[1..] {
+          if p.trim().starts_with("rake") {
+            hh.rake = first_amount(p).unwrap_or(0.0);
+          }
+        }
+      } else if lower.starts_with("board") {
+        let board = parse_bracket_cards(line);
+        if board.len() > hh.board.len() {
+          hh.board = board;
+        }
+      }
+      continue;
+    }
+
+    if let Some(rest) = line.strip_prefix("Table '") {
+      // Table 'Alpha' 6-max Seat #3 is the button
+      if let Some(end) = rest.find('\'') {
+        hh.table = rest[..end].to_string();
+      }
+      if let Some(idx) = line.find("-max") {
+        let digits: String = line[..idx].chars().rev().take_while(|c| c.is_ascii_digit()).collect();
+        hh.max_players = digits.chars().rev().collect::<String>().parse().unwrap_or(0);
+      }
+      if let Some(idx) = line.find("Seat #") {
+        hh.button_seat = line[idx+6..].split_whitespace().next().unwrap_or("0").parse().unwrap_or(0);
+      }
+      continue;
+    }
+    if line.starts_with("Seat ") && state.street == Street::Preflop && !line.contains(" : ") {
+      parse_seat_line(&mut hh, line);
+      continue;
+    }
+    if let Some(rest) = line.strip_prefix("Dealt to ") {
+      // Dealt to Hero [Ah Kd]
+      if let Some(open) = rest.find('[') {
+        let name = rest[..open].trim().to_string();
+        let cards = parse_bracket_cards(rest);
+        if !cards.is_empty() {
+          hh.hole_cards.insert(name.clone(), cards);
+          hh.hero = Some(name);
+        }
+      }
+      continue;
+    }
+    if line.starts_with("Uncalled bet (") {
+      // Uncalled bet ($0.10) returned to player3
+      let amount = first_amount(&line[13..]).unwrap_or(0.0);
+      if let Some(idx) = line.find(" returned to ") {
+        let player = line[idx+13..].trim().to_string();
+        push_action(&mut hh, &mut state, &player, ActionKind::Return, amount, false);
+      }
+      continue;
+    }
+    if site == Site::PokerStars && line.contains(" collected ") && !line.contains(": ") {
+      // player3 collected $0.50 from pot
+      let idx = line.find(" collected ").unwrap();
+      let amount = first_amount(&line[idx+11..]).unwrap_or(0.0);
+      hh.collected.push((line[..idx].to_string(), amount));
+      continue;
+    }
+
+    if let Some((player_part, action)) = split_player_line(site, line) {
+      let (player, is_hero) = clean_player_name(player_part);
+      if is_hero {
+        hh.hero = Some(player.clone());
+      }
+      if action.to_lowercase().starts_with("set dealer") {
+        // Ignition: "Dealer : Set dealer [6]", seat of the button is in brackets
+        if let Some(open) = action.find('[') {
+          hh.button_seat = action[open+1..].trim_end_matches(']').trim().parse().unwrap_or(0);
+        }
+        continue;
+      }
+      parse_action(&mut hh, &mut state, &player, action);
+    }
+  }
+
+  if hh.seats.is_empty() {
+    return Err(invalid_data(format!("hand {} has no seats", hh.hand_id)))
+  }
+  if site == Site::Ignition && hh.button_seat == 0 {
+    if let Some(s) = hh.seats.iter().find(|s| s.player == "Dealer") {
+      hh.button_seat = s.seat;
+    }
+  }
+  if hh.max_players == 0 {
+    hh.max_players = hh.seats.len();
+  }
+  return Ok(hh)
+}
+
+// splits a file with many hands into separate hand texts, using header line of each hand
+fn split_hands(text: &str) -> Vec<String> {
+  let mut res = Vec::new();
+  let mut current = String::new();
+  for line in text.lines() {
+    let line = line.trim_start_matches('\u{feff}');
+    if detect_site(line.trim()).is_some() && !current.trim().is_empty() {
+      res.push(current);
+      current = String::new();
+    }
+    current.push_str(line.trim());
+    current.push('\n');
+  }
+  if !current.trim().is_empty() {
+    res.push(current);
+  }
+  return res
+}
+
+// parses every hand in a text, hands that fail to parse are reported and skipped
+pub fn parse_hand_histories(text: &str) -> Vec<HandHistory> {
+  let mut res = Vec::new();
+  for hand_text in split_hands(text) {
+    match parse_hand_history(&hand_text) {
+      Ok(hh) => res.push(hh),
+      Err(e) => println!("Skipping hand: {}", e),
+    }
+  }
+  return res
+}
+
+// imports a single file, or every .txt file in a directory (recursively)
+pub fn import_hand_histories(path: &Path) -> Result<Vec<HandHistory>, Error> {
+  let mut res = Vec::new();
+  if path.is_dir() {
+    let mut entries: Vec<_> = fs::read_dir(path)?.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    entries.sort();
+    for entry in entries {
+      if entry.is_dir() || entry.extension().map(|e| e == "txt").unwrap_or(false) {
+        res.extend(import_hand_histories(&entry)?);
+      }
+    }
+  } else {
+    let bytes = fs::read(path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    res.extend(parse_hand_histories(&text));
+  }
+  return Ok(res)
+}
+
+pub fn print_import_summary(hands: &Vec<HandHistory>) {
+  let mut per_site = HashMap::<String, usize>::new();
+  let mut hero_net = 0.0;
+  let mut hero_hands = 0;
+  for hh in hands {
+    *per_site.entry(hh.site.to_string()).or_insert(0) += 1;
+    if let Some(hero) = &hh.hero {
+      hero_net += hh.net_result(hero);
+      hero_hands += 1;
+    }
+  }
+  println!("Imported {} hands", hands.len());
+  let mut sites: Vec<&String> = per_site.keys().collect();
+  sites.sort();
+  for site in sites {
+    println!("{:<20}:{}", site, per_site[site]);
+  }
+  if hero_hands > 0 {
+    println!("Hero played {} hands, net: ${:+.2}", hero_hands, hero_net);
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+
+  pub(crate) const POKERSTARS_HAND: &str = "PokerStars Hand #219876543210: Hold'em No Limit ($0.01/$0.02 USD) - 2020/12/20 12:00:00 ET
+Table 'Alpha' 6-max Seat #3 is the button
+Seat 1: player1 ($2 in chips)
+Seat 2: Hero ($2.10 in chips)
+Seat 3: player3 ($1.50 in chips)
+Seat 5: player5 ($3 in chips) is sitting out
+player1: posts small blind $0.01
+Hero: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kd]
+player3: raises $0.04 to $0.06
+player1: folds
+Hero: calls $0.04
+*** FLOP *** [7s 8c 2s]
+Hero: checks
+player3: bets $0.10
+Hero: raises $0.20 to $0.30
+player3: folds
+Uncalled bet ($0.20) returned to Hero
+Hero collected $0.32 from pot
+Hero: doesn't show hand
+*** SUMMARY ***
+Total pot $0.33 | Rake $0.01
+Board [7s 8c 2s]
+Seat 1: player1 (small blind) folded before Flop
+Seat 2: Hero (big blind) collected ($0.32)
+Seat 3: player3 (button) folded on the Flop
+";
+
+  const IGNITION_HAND: &str = "Ignition Hand #4071234567 TBL#18374213 HOLDEM No Limit - 2020-12-20 12:00:00
+Seat 1: Small Blind ($2 in chips)
+Seat 2: Big Blind [ME] ($2.10 in chips)
+Seat 3: Dealer ($1.50 in chips)
+Dealer : Set dealer [3]
+Small Blind : Small Blind $0.01
+Big Blind  [ME] : Big blind $0.02
+*** HOLE CARDS ***
+Small Blind : Card dealt to a spot [7c 2d]
+Big Blind  [ME] : Card dealt to a spot [Ah Kd]
+Dealer : Card dealt to a spot [Qs Qh]
+Dealer : Raises $0.06 to $0.06
+Small Blind : Folds
+Big Blind  [ME] : Call $0.04
+*** FLOP *** [7s 8c 2s]
+Big Blind  [ME] : Check
+Dealer : All-in $1.44
+Big Blind  [ME] : Call $1.44
+*** TURN *** [7s 8c 2s] [Qd]
+*** RIVER *** [7s 8c 2s Qd] [3h]
+Dealer : Showdown [Qs Qh Qd 8c 7s] (Three of a kind)
+Big Blind  [ME] : Showdown [Ah Kd Qd 8c 7s] (High Card)
+Dealer : Hand Result $2.99
+*** SUMMARY ***
+Total Pot($3.01)
+Board [7s 8c 2s Qd 3h]
+Seat+1: Small Blind Folded before the FLOP
+Seat+2: Big Blind lose with High Card
+Seat+3: Dealer $2.99 [Qs Qh] won
+";
+
+  fn close(a: f32, b: f32) -> bool {
+    return (a-b).abs() < 1e-4
+  }
+
+  #[test]
+  fn pokerstars_hand() {
+    let hh = parse_hand_history(POKERSTARS_HAND).unwrap();
+    assert!(hh.site == Site::PokerStars);
+    assert_eq!(hh.hand_id, "219876543210");
+    assert_eq!(hh.table, "Alpha");
+    assert_eq!((hh.max_players, hh.button_seat), (6, 3));
+    assert!(close(hh.small_blind, 0.01) && close(hh.big_blind, 0.02));
+    assert_eq!(hh.seats.iter().map(|s| s.player.as_str()).collect::<Vec<&str>>(), vec!["player1", "Hero", "player3"]);
+    assert!(close(hh.seats[1].stack, 2.10));
+    assert_eq!(hh.hero.as_deref(), Some("Hero"));
+    assert_eq!(hh.hero_cards().unwrap(), &conv_string_to_cards("Ah Kd"));
+    assert_eq!(hh.board, conv_string_to_cards("7s 8c 2s"));
+    assert!(close(hh.total_pot, 0.33) && close(hh.rake, 0.01));
+
+    let flop = hh.street_actions(Street::Flop);
+    let kinds: Vec<ActionKind> = flop.iter().map(|a| a.kind).collect();
+    assert!(kinds == vec![ActionKind::Check, ActionKind::Bet, ActionKind::Raise, ActionKind::Fold, ActionKind::Return]);
+    assert!(close(flop[2].amount, 0.30) && close(flop[2].to, 0.30));
+    assert!(close(flop[4].amount, 0.20) && close(flop[4].to, 0.10));
+
+    // put in 0.02 + 0.04 + 0.30 - 0.20, collected 0.32
+    assert!(close(hh.net_result("Hero"), 0.16));
+    assert!(close(hh.net_result("player3"), -0.16));
+    assert!(close(hh.net_result("player1"), -0.01));
+    assert!(hh.position_of("player3").is_some());
+    assert!(hh.position_of("Hero").is_none());
+  }
+
+  #[test]
+  fn ignition_hand() {
+    let hh = parse_hand_history(IGNITION_HAND).unwrap();
+    assert!(hh.site == Site::Ignition);
+    assert_eq!(hh.hand_id, "4071234567");
+    assert_eq!(hh.table, "TBL#18374213");
+    assert_eq!((hh.max_players, hh.button_seat), (3, 3));
+    assert!(close(hh.small_blind, 0.01) && close(hh.big_blind, 0.02));
+    assert_eq!(hh.seats.iter().map(|s| s.player.as_str()).collect::<Vec<&str>>(), vec!["Small Blind", "Big Blind", "Dealer"]);
+    assert_eq!(hh.hero.as_deref(), Some("Big Blind"));
+    assert_eq!(hh.hero_cards().unwrap(), &conv_string_to_cards("Ah Kd"));
+    // showdown line lists the best five, hole cards stay the dealt ones
+    assert_eq!(hh.hole_cards["Dealer"], conv_string_to_cards("Qs Qh"));
+    assert_eq!(hh.board, conv_string_to_cards("7s 8c 2s Qd 3h"));
+    assert!(close(hh.total_pot, 3.01));
+    assert_eq!(hh.showdown, vec!["Dealer", "Big Blind"]);
+
+    let flop = hh.street_actions(Street::Flop);
+    let kinds: Vec<ActionKind> = flop.iter().map(|a| a.kind).collect();
+    // all-in with nothing to call is a bet
+    assert!(kinds == vec![ActionKind::Check, ActionKind::Bet, ActionKind::Call]);
+    assert!(flop[1].all_in);
+    assert!(close(hh.net_result("Big Blind"), -1.50));
+    assert!(close(hh.net_result("Dealer"), 1.49));
+  }
+
+  #[test]
+  fn ante_is_matched_by_the_action() {
+    let text = "PokerStars Hand #219876543211: Hold'em No Limit ($0.01/$0.02 USD) - 2020/12/20 12:01:00 ET
+Table 'Alpha' 6-max Seat #1 is the button
+Seat 1: Dante ($2 in chips)
+Seat 2: Santeria ($2 in chips)
+Seat 3: Hero ($2 in chips)
+Dante: posts the ante $0.01
+Santeria: posts the ante $0.01
+Hero: posts the ante $0.01
+Santeria: posts small blind $0.01
+Hero: posts big blind $0.02
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kd]
+Dante: calls $0.02
+Santeria: raises $0.04 to $0.06
+Hero: folds
+Dante: folds
+Uncalled bet ($0.04) returned to Santeria
+Santeria collected $0.09 from pot
+*** SUMMARY ***
+Total pot $0.09 | Rake $0
+";
+    let hh = parse_hand_history(text).unwrap();
+    assert!(close(hh.ante, 0.01));
+    let kinds: Vec<ActionKind> = hh.street_actions(Street::Preflop).iter().map(|a| a.kind).collect();
+    assert!(kinds == vec![ActionKind::Ante, ActionKind::Ante, ActionKind::Ante, ActionKind::SmallBlind, ActionKind::BigBlind,
+      ActionKind::Call, ActionKind::Raise, ActionKind::Fold, ActionKind::Fold, ActionKind::Return]);
+    assert!(close(hh.net_result("Dante"), -0.03));
+    assert!(close(hh.net_result("Santeria"), 0.06));
+  }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use std::time::SystemTime;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+// number of simulated futures averaged by future game simulation
+const FGS_SIMULATIONS: usize = 2000;
+// share of simulated hands where two players get all in, the rest are won uncontested
+const FGS_ALL_IN_CHANCE: f32 = 0.2;
+// finished players are bits of a u64 in icm_equities
+pub const MAX_PLAYERS: usize = 64;
+
+// Malmuth-Harville: chance to finish first is stack share, and each next place is the same among the players left.
+// goes through sets of players that took the paid places so far, so it's cheap as long as there are few paid places.
+// meant for sit & go's and final tables, up to MAX_PLAYERS players with chips.
+pub fn icm_equities(stacks: &[f32], payouts: &[f32]) -> Vec<f32> {
+  let alive: Vec<usize> = (0..stacks.len()).filter(|i| stacks[*i] > 0.0).collect();
+  let mut equities = vec![0.0; stacks.len()];
+  let places = payouts.len().min(alive.len());
+  let total: f32 = alive.iter().map(|i| stacks[*i]).sum();
+  // finished players are a bit mask over alive
+  let mut layer: HashMap<u64, (f32, f32)> = HashMap::new();
+  layer.insert(0, (1.0, total));
+  for payout in payouts.iter().take(places) {
+    let mut next: HashMap<u64, (f32, f32)> = HashMap::new();
+    for (mask, (prob, chips_left)) in layer {
+      for (bit, i) in alive.iter().enumerate() {
+        if mask & (1 << bit) != 0 {
+          continue;
+        }
+        let p = prob*stacks[*i]/chips_left;
+        equities[*i] += p*payout;
+        let entry = next.entry(mask | (1 << bit)).or_insert((0.0, chips_left - stacks[*i]));
+        entry.0 += p;
+      }
+    }
+    layer = next;
+  }
+  // busted players took the worst places, without knowing the order they share them
+  let busted: Vec<usize> = (0..stacks.len()).filter(|i| stacks[*i] <= 0.0).collect();
+  if !busted.is_empty() && alive.len() < payouts.len() {
+    let end = payouts.len().min(alive.len() + busted.len());
+    let share: f32 = payouts[alive.len()..end].iter().sum::<f32>()/busted.len() as f32;
+    for i in busted {
+      equities[i] = share;
+    }
+  }
+  return equities
+}
+
+// blinds and how many hands to look ahead for future game simulation
+#[derive(Clone, Copy)]
+pub struct FutureGame {
+  pub big_blind: f32,
+  pub hands: usize,
+}
+
+// Plays simplified future hands before applying ICM: blinds go around, and every hand either two random players
+// get all in as a coin flip, or a random player wins the blinds. Short stacks get blinded down and bust more
+// often than plain ICM thinks, which is what matters close to the money.
+pub fn fgs_equities(stacks: &[f32], payouts: &[f32], future: FutureGame, seed: u64) -> Vec<f32> {
+  let mut rng = ChaCha20Rng::seed_from_u64(seed);
+  let mut equities = vec![0.0; stacks.len()];
+  let n = stacks.len();
+  for _ in 0..FGS_SIMULATIONS {
+    let mut s = stacks.to_vec();
+    let mut button = rng.gen_range(0..n);
+    for _ in 0..future.hands {
+      let alive: Vec<usize> = (0..n).filter(|i| s[*i] > 0.0).collect();
+      if alive.len() <= 1 {
+        break;
+      }
+      button = (button+1) % n;
+      while s[button] == 0.0 {
+        button = (button+1) % n;
+      }
+      let pos = alive.iter().position(|i| *i == button).unwrap();
+      let sb = alive[(pos+1) % alive.len()];
+      let bb = alive[(pos+2) % alive.len()];
+      let mut pot = 0.0;
+      let mut invested = vec![0.0; n];
+      for (seat, blind) in [(sb, future.big_blind*0.5), (bb, future.big_blind)] {
+        let posted = blind.min(s[seat]);
+        s[seat] -= posted;
+        invested[seat] += posted;
+        pot += posted;
+      }
+      if rng.gen::<f32>() < FGS_ALL_IN_CHANCE {
+        let a = alive[rng.gen_range(0..alive.len())];
+        let mut b = alive[rng.gen_range(0..alive.len())];
+        while b == a {
+          b = alive[rng.gen_range(0..alive.len())];
+        }
+        let amount = (s[a]+invested[a]).min(s[b]+invested[b]);
+        for seat in [a, b] {
+          let more = (amount - invested[seat]).max(0.0);
+          s[seat] -= more;
+          pot += more;
+        }
+        let winner = if rng.gen::<bool>() { a } else { b };
+        s[winner] += pot;
+      } else {
+        let winner = alive[rng.gen_range(0..alive.len())];
+        s[winner] += pot;
+      }
+      // players that busted this hand take the worst places left, shared when there are more of them
+      let busted: Vec<usize> = alive.iter().filter(|i| s[**i] <= 0.0).cloned().collect();
+      let left = alive.len() - busted.len();
+      let end = payouts.len().min(alive.len());
+      if left < end {
+        let share: f32 = payouts[left..end].iter().sum::<f32>()/busted.len() as f32;
+        for i in busted {
+          equities[i] += share/FGS_SIMULATIONS as f32;
+        }
+      }
+    }
+    // players that busted in this simulation are already paid
+    let eq = icm_equities(&s, payouts);
+    for i in 0..n {
+      if s[i] > 0.0 {
+        equities[i] += eq[i]/FGS_SIMULATIONS as f32;
+      }
+    }
+  }
+  return equities
+}
+
+// Payouts and stacks behind of everyone left in a tournament. Hero's stack is first and the next one is
+// the opponent hero is playing the pot against, that opponent gets the chips when hero loses.
+// example: "50,30,20/1500,2200,800,3000", with future game simulation at 100 big blind for 20 hands: "50,30,20/1500,2200,800,3000/100:20"
+pub struct TournamentContext {
+  pub payouts: Vec<f32>,
+  pub stacks: Vec<f32>,
+  pub future_game: Option<FutureGame>,
+}
+
+fn parse_amounts(s: &str) -> Result<Vec<f32>, Error> {
+  return s.split(',').map(|v| {
+    let v = v.trim().replace('$', "");
+    lexical::parse::<f32, _>(&v).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount '{}': {:?}", v, e)))
+  }).collect()
+}
+
+impl FromStr for TournamentContext {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let parts: Vec<&str> = s.trim().split('/').collect();
+    if parts.len() != 2 && parts.len() != 3 {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("expecting payouts/stacks[/big blind:hands], got: '{}'", s)))
+    }
+    let payouts = parse_amounts(parts[0])?;
+    let stacks = parse_amounts(parts[1])?;
+    if stacks.len() < 2 {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("need stacks of hero and at least one opponent, got: '{}'", parts[1])))
+    }
+    if stacks.len() > MAX_PLAYERS {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("ICM works for up to {} players, got: {}", MAX_PLAYERS, stacks.len())))
+    }
+    let future_game = match parts.get(2) {
+      Some(f) => {
+        let (bb, hands) = f.split_once(':').ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("expecting big blind:hands, got: '{}'", f)))?;
+        let big_blind = parse_amounts(bb)?[0];
+        let hands = hands.trim().parse::<usize>().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        Some(FutureGame{big_blind: big_blind, hands: hands})
+      },
+      None => None,
+    };
+    return Ok(TournamentContext{payouts: payouts, stacks: stacks, future_game: future_game})
+  }
+}
+
+impl TournamentContext {
+  pub fn equities(&self, stacks: &[f32]) -> Vec<f32> {
+    return match self.future_game {
+      // same seed for every call, so differences between outcomes aren't simulation noise
+      Some(future) => fgs_equities(stacks, &self.payouts, future, 0),
+      None => icm_equities(stacks, &self.payouts),
+    }
+  }
+
+  // hero's prize equity after hero and the opponent win or lose chips
+  fn hero_equity(&self, hero_delta: f32, villain_delta: f32) -> f32 {
+    let mut stacks = self.stacks.clone();
+    stacks[0] = (stacks[0] + hero_delta).max(0.0);
+    stacks[1] = (stacks[1] + villain_delta).max(0.0);
+    return self.equities(&stacks)[0]
+  }
+
+  // $EV of calling compared to folding. when hero folds, the opponent gets the pot
+  pub fn call_dollar_ev(&self, total_pot: f32, call_amount: f32, win_ch: f32) -> f32 {
+    let call_amount = call_amount.min(self.stacks[0]);
+    let fold = self.hero_equity(0.0, total_pot);
+    let win = self.hero_equity(total_pot, 0.0);
+    let lose = self.hero_equity(-call_amount, total_pot + call_amount);
+    return win_ch*win + (1.0-win_ch)*lose - fold
+  }
+
+  // $EV of betting or raising compared to folding, the opponent folds with fold_ch or calls the bet
+  pub fn raise_dollar_ev(&self, total_pot: f32, bet_amount: f32, win_ch: f32, fold_ch: f32) -> f32 {
+    let bet_amount = bet_amount.min(self.stacks[0]).min(self.stacks[1]);
+    let fold = self.hero_equity(0.0, total_pot);
+    let steal = self.hero_equity(total_pot, 0.0);
+    let win = self.hero_equity(total_pot + bet_amount, -bet_amount);
+    let lose = self.hero_equity(-bet_amount, total_pot + bet_amount);
+    return fold_ch*steal + (1.0-fold_ch)*(win_ch*win + (1.0-win_ch)*lose) - fold
+  }
+}
+
+pub fn print_icm(context: &TournamentContext) {
+  let chips: f32 = context.stacks.iter().sum();
+  let icm = icm_equities(&context.stacks, &context.payouts);
+  let fgs = context.future_game.map(|f| fgs_equities(&context.stacks, &context.payouts, f, SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()));
+  for (i, stack) in context.stacks.iter().enumerate() {
+    let mut line = format!("Player {:<2} {:>10.0} chips ({:5.1}%): ICM ${:.2}", i+1, stack, stack/chips*100.0, icm[i]);
+    if let Some(fgs) = &fgs {
+      line += &format!(", FGS ${:.2}", fgs[i]);
+    }
+    println!("{}", line);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(actual: &[f32], expected: &[f32]) {
+    for (a, e) in actual.iter().zip(expected.iter()) {
+      assert!((a-e).abs() < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+  }
+
+  #[test]
+  fn heads_up() {
+    // chance to win is the stack share
+    assert_close(&icm_equities(&[3000.0, 1000.0], &[70.0, 30.0]), &[60.0, 40.0]);
+  }
+
+  #[test]
+  fn three_players() {
+    assert_close(&icm_equities(&[5000.0, 3000.0, 2000.0], &[50.0, 30.0, 20.0]), &[38.393, 32.75, 28.857]);
+  }
+
+  #[test]
+  fn too_many_players() {
+    let stacks = vec!["100"; MAX_PLAYERS+1].join(",");
+    assert!(format!("50,30,20/{}", stacks).parse::<TournamentContext>().is_err());
+    let stacks = vec!["100"; MAX_PLAYERS].join(",");
+    assert!(format!("50,30,20/{}", stacks).parse::<TournamentContext>().is_ok());
+  }
+
+  #[test]
+  fn equal_stacks_split_evenly() {
+    assert_close(&icm_equities(&[1000.0; 4], &[50.0, 30.0, 20.0]), &[25.0; 4]);
+  }
+}
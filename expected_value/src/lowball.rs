@@ -0,0 +1,196 @@
+use std::fmt;
+use itertools::Itertools;
+
+// 8 or better: all five low cards have to be eight or lower, with no pairs
+pub const EIGHT_OR_BETTER: u8 = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LowKind {
+  // ace is low, straights and flushes don't count, best hand is A-2-3-4-5
+  AceToFive,
+  // ace is high, straights and flushes count against, best hand is 7-5-4-3-2
+  DeuceToSeven,
+}
+
+impl fmt::Display for LowKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      LowKind::AceToFive => write!(f, "A-5"),
+      LowKind::DeuceToSeven => write!(f, "2-7"),
+    }
+  }
+}
+
+// Low hand of 5 cards as a number where lower is better: a category (pairs, and for 2-7 also straights and
+// flushes) and then ranks from the most important one down, in base 15.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LowHand {
+  pub kind_value: u32,
+  // ranks from highest to lowest, ace is 1 in A-5 low
+  pub ranks: [u8; 5],
+}
+
+impl fmt::Display for LowHand {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // ace is 1 in A-5 low and 14 in 2-7
+    let names: Vec<String> = self.ranks.iter().map(|r| (b"?A23456789TJQKA"[*r as usize] as char).to_string()).collect();
+    write!(f, "{}", names.join("-"))
+  }
+}
+
+// cards are indices from card_index()
+pub fn low_hand(cards: &[u8; 5], kind: LowKind) -> LowHand {
+  let mut ranks: Vec<u8> = cards.iter().map(|c| c/4 + 2).collect();
+  if kind == LowKind::AceToFive {
+    for r in ranks.iter_mut() {
+      if *r == 14 {
+        *r = 1;
+      }
+    }
+  }
+  let mut counts = [0u8; 15];
+  for r in &ranks {
+    counts[*r as usize] += 1;
+  }
+  // pairs first, then the rest, each from high to low
+  ranks.sort_by(|a, b| counts[*b as usize].cmp(&counts[*a as usize]).then(b.cmp(a)));
+  let max_count = ranks.iter().map(|r| counts[*r as usize]).max().unwrap();
+  let num_groups = counts.iter().filter(|c| **c > 0).count();
+  let mut category = match (max_count, num_groups) {
+    (1, _) => 0,
+    (2, 4) => 1,
+    (2, _) => 2,
+    (3, 3) => 3,
+    (3, _) => 6,
+    _ => 7,
+  };
+  if kind == LowKind::DeuceToSeven && max_count == 1 {
+    let is_straight = ranks[0] - ranks[4] == 4;
+    let is_flush = cards.iter().all(|c| c % 4 == cards[0] % 4);
+    category = match (is_straight, is_flush) {
+      (true, true) => 8,
+      (false, true) => 5,
+      (true, false) => 4,
+      _ => 0,
+    };
+  }
+  let mut value = category;
+  for r in &ranks {
+    value = value*15 + *r as u32;
+  }
+  return LowHand{kind_value: value, ranks: [ranks[0], ranks[1], ranks[2], ranks[3], ranks[4]]}
+}
+
+fn qualifies(hand: &LowHand, qualifier: Option<u8>) -> bool {
+  return match qualifier {
+    // no pairs and highest card within the limit
+    Some(q) => hand.ranks[0] <= q && hand.ranks.windows(2).all(|w| w[0] != w[1]),
+    None => true,
+  }
+}
+
+// best low out of any 5 of 5 to 7 cards, None when nothing qualifies
+pub fn best_low(cards: &[u8], kind: LowKind, qualifier: Option<u8>) -> Option<LowHand> {
+  return cards.iter().cloned().combinations(5)
+    .map(|c| low_hand(&[c[0], c[1], c[2], c[3], c[4]], kind))
+    .filter(|h| qualifies(h, qualifier))
+    .min()
+}
+
+// Omaha low uses exactly two hole cards and three board cards, same as the high hand
+pub fn omaha_best_low(hole: &[u8], board: &[u8], qualifier: Option<u8>) -> Option<LowHand> {
+  let mut best: Option<LowHand> = None;
+  for h in hole.iter().combinations(2) {
+    for b in board.iter().combinations(3) {
+      let hand = low_hand(&[*h[0], *h[1], *b[0], *b[1], *b[2]], LowKind::AceToFive);
+      if qualifies(&hand, qualifier) && best.is_none_or(|best| hand < best) {
+        best = Some(hand);
+      }
+    }
+  }
+  return best
+}
+
+// Share of the pot for every player in a hi/lo split game. Half goes to the best high hand and half to the best
+// qualifying low, ties split their half, so one of two equal lows gets a quarter. Without a qualifying low high takes it all.
+pub fn hi_lo_shares(high: &[f32], low: &[Option<LowHand>]) -> Vec<f32> {
+  let mut shares = vec![0.0; high.len()];
+  let best_high = high.iter().cloned().fold(f32::MIN, f32::max);
+  let best_low = low.iter().flatten().min().cloned();
+  let high_half = if best_low.is_some() { 0.5 } else { 1.0 };
+  let high_winners: Vec<usize> = (0..high.len()).filter(|i| high[*i] == best_high).collect();
+  for i in &high_winners {
+    shares[*i] += high_half/high_winners.len() as f32;
+  }
+  if let Some(best_low) = best_low {
+    let low_winners: Vec<usize> = (0..low.len()).filter(|i| low[*i] == Some(best_low)).collect();
+    for i in &low_winners {
+      shares[*i] += 0.5/low_winners.len() as f32;
+    }
+  }
+  return shares
+}
+
+// both low values of a hand, and whether it makes an 8 or better low
+pub fn print_low_hand(cards: &[u8]) {
+  for kind in [LowKind::AceToFive, LowKind::DeuceToSeven] {
+    let hand = best_low(cards, kind, None).unwrap();
+    println!("{} low: {}", kind, hand);
+  }
+  match best_low(cards, LowKind::AceToFive, Some(EIGHT_OR_BETTER)) {
+    Some(hand) => println!("8 or better: {}", hand),
+    None => println!("8 or better: no low"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::card_index;
+
+  fn low(s: &str, kind: LowKind) -> LowHand {
+    let c: Vec<u8> = conv_string_to_cards(s).iter().map(card_index).collect();
+    return low_hand(&[c[0], c[1], c[2], c[3], c[4]], kind)
+  }
+
+  #[test]
+  fn ace_to_five_categories() {
+    let kind = LowKind::AceToFive;
+    let hands = ["Ah 2s 3c 4d 5h", "2h 3s 4c 5d 6h", "Ah 2s 3c 4d 8h", "Ah As 2c 3d 4h", "Ah As 2c 2d 4h",
+      "Ah As Ac 2d 3h", "Ah As Ac 2d 2h", "Ah As Ac Ad 2h"];
+    for w in hands.windows(2) {
+      assert!(low(w[0], kind) < low(w[1], kind), "{} should be a better low than {}", w[0], w[1]);
+    }
+    // straights and flushes don't count
+    assert!(low("Ah 2h 3h 4h 5h", kind) == low("Ah 2s 3c 4d 5h", kind));
+  }
+
+  #[test]
+  fn deuce_to_seven_categories() {
+    let kind = LowKind::DeuceToSeven;
+    let hands = ["7h 5s 4c 3d 2h", "Kh 5s 4c 3d 2h", "Ah 5s 4c 3d 2h", "2h 2s 3c 4d 5h", "2h 2s 3c 3d 5h",
+      "2h 2s 2c 3d 4h", "2h 3s 4c 5d 6h", "2h 4h 5h 6h 8h", "2h 2s 2c 3d 3h", "2h 2s 2c 2d 3h", "2h 3h 4h 5h 6h"];
+    for w in hands.windows(2) {
+      assert!(low(w[0], kind) < low(w[1], kind), "{} should be a better low than {}", w[0], w[1]);
+    }
+  }
+
+  #[test]
+  fn qualifier() {
+    let cards: Vec<u8> = conv_string_to_cards("Ah 2s 9c Td Jh Kd Qs").iter().map(card_index).collect();
+    assert!(best_low(&cards, LowKind::AceToFive, Some(EIGHT_OR_BETTER)).is_none());
+    let cards: Vec<u8> = conv_string_to_cards("Ah 2s 3c 8d Jh Kd 7s").iter().map(card_index).collect();
+    assert_eq!(best_low(&cards, LowKind::AceToFive, Some(EIGHT_OR_BETTER)).unwrap().ranks, [8, 7, 3, 2, 1]);
+  }
+
+  #[test]
+  fn hi_lo_shares_quartering() {
+    let l = low("Ah 2s 3c 4d 5h", LowKind::AceToFive);
+    // best high and one of two equal lows: half plus a quarter
+    assert_eq!(hi_lo_shares(&[300.0, 200.0], &[Some(l), Some(l)]), vec![0.75, 0.25]);
+    assert_eq!(hi_lo_shares(&[300.0, 200.0, 100.0], &[None, Some(l), Some(l)]), vec![0.5, 0.25, 0.25]);
+    // no qualifying low, high scoops
+    assert_eq!(hi_lo_shares(&[300.0, 300.0], &[None, None]), vec![0.5, 0.5]);
+  }
+}
@@ -1,5 +1,7 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
+// explicit returns and `field: field` initialisers are the style of this code base
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
 
 use std::fs;
 use std::fs::File;
@@ -21,6 +23,32 @@ use rand::{RngCore, Rng, SeedableRng};
 use rand_chacha::{ChaCha20Core,ChaCha20Rng};
 use csv::Writer;
 
+mod score_table;
+mod preflop_matrix;
+mod board_texture;
+mod draws;
+mod runouts;
+mod contribution;
+mod hand_history;
+mod review;
+mod player_stats;
+mod range_narrowing;
+mod opponent_profile;
+mod game_engine;
+mod bots;
+mod self_play;
+mod solver;
+mod push_fold;
+mod icm;
+mod omaha;
+mod short_deck;
+mod lowball;
+mod stud;
+mod variant;
+mod range_equity;
+mod blockers;
+mod nut_rank;
+
 // for 6 player table:
 // SB, BB, UTG - Early
 // 4th pos = Mid
@@ -94,6 +122,7 @@ impl fmt::Debug for CardSuit {
   }
 }
 
+#[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Hash, Clone, Copy, Serialize, Deserialize)]
 struct Card {
   rank: u8, // [2..14]
@@ -325,6 +354,7 @@ fn conv_string_to_cards(s: &str) -> Vec<Card> {
   return res
 }
 
+#[allow(clippy::ptr_arg, clippy::needless_range_loop, clippy::assign_op_pattern)]
 fn find_common_cards_in_pack(cards: &Vec<&Card>, pack: &Vec<Card>) -> usize {
   let mut common = 0;
   for i in 0..cards.len() {
@@ -339,6 +369,7 @@ fn find_common_cards_in_pack(cards: &Vec<&Card>, pack: &Vec<Card>) -> usize {
   return common
 }
 
+#[allow(clippy::ptr_arg, clippy::needless_range_loop, clippy::assign_op_pattern)]
 fn find_common_cards_in_pack_no_ref(cards: &Vec<Card>, pack: &Vec<Card>) -> usize {
   let mut common = 0;
   for i in 0..cards.len() {
@@ -353,6 +384,7 @@ fn find_common_cards_in_pack_no_ref(cards: &Vec<Card>, pack: &Vec<Card>) -> usiz
   return common
 }
 
+#[allow(clippy::ptr_arg, clippy::for_kv_map)]
 fn find_possible_hands_in_all_combinations(cards: &Vec<&Card>, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> Vec<Vec<Card>> {
   let mut possible_hands = Vec::new();
   for (k, _) in combinations {
@@ -363,6 +395,7 @@ fn find_possible_hands_in_all_combinations(cards: &Vec<&Card>, combinations: &Ha
   return possible_hands
 }
 
+#[allow(clippy::ptr_arg, clippy::for_kv_map)]
 fn find_possible_hands_in_all_combinations_no_ref(cards: &Vec<Card>, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> Vec<Vec<Card>> {
   let mut possible_hands = Vec::new();
   for (k, _) in combinations {
@@ -373,7 +406,6 @@ fn find_possible_hands_in_all_combinations_no_ref(cards: &Vec<Card>, combination
   return possible_hands
 }
 
-#[allow(illegal_floating_point_literal_pattern)]
 fn get_best_hand_string(f: f32) -> HandRank {
   return match f {
     0.0..=99.999 =>    HandRank::HighCard,
@@ -390,63 +422,36 @@ fn get_best_hand_string(f: f32) -> HandRank {
   };
 }
 
-fn is_hand_part_of_made_up_hand(hand: &Vec<Card>, hand_rank: &HandRank, combination: &Vec<Card>) -> bool {
-  let mut community_cards = combination.clone();
-  community_cards.retain(|&x| x != hand[0]);
-  community_cards.retain(|&x| x != hand[1]);
+// checks if hole cards are part of what makes hand_rank in a 5 card combination,
+// and not just kickers or board cards playing on their own.
+// Kd Ad vs 7h 7d Ah -> true, aces are paired with a hole card.
+// Kd Qd vs 7h 7d Ah -> false, pair of sevens comes from the board and Kd Qd are kickers.
+fn is_hand_part_of_made_up_hand(hand: &[Card], hand_rank: &HandRank, combination: &[Card]) -> bool {
+  let playing_cards: Vec<&Card> = hand.iter().filter(|c| combination.contains(c)).collect();
+  if playing_cards.is_empty() {
+    return false
+  }
 
   return match hand_rank {
     HandRank::HighCard => {
       true
     },
-    HandRank::Pair => {
+    HandRank::Pair | HandRank::TwoPairs | HandRank::ThreeOfAKind | HandRank::FullHouse | HandRank::FourOfAKind => {
+      // at least one hole card has to be in a paired rank
       let mut res = false;
-      if hand[0].rank == hand[1].rank {
-        res = true;
-      }
-      for i in 0..hand.len() {
-        for j in 0..community_cards.len() {
-          if hand[i].rank == community_cards[j].rank {
-            res = true;
-          }
+      for h in &playing_cards {
+        if combination.iter().filter(|c| c.rank == h.rank).count() >= 2 {
+          res = true;
         }
       }
       res
     },
-    HandRank::TwoPairs => {
-      let mut num_matched = 0;
-      for i in 0..hand.len() {
-        for j in 0..community_cards.len() {
-          if hand[i].rank == community_cards[j].rank {
-            num_matched += 1;
-          }
-        }
-      }
-      num_matched == 2 // Kd Ad vs 7h 7d Ah <- will skip this case, intentionally
-    },
-    HandRank::ThreeOfAKind => {
-      let mut num_matched = 0;
-      for i in 0..hand.len() {
-        for j in 0..community_cards.len() {
-          if hand[i].rank == community_cards[j].rank {
-            num_matched += 1;
-          }
-        }
-      }
-      num_matched == 2 // 2 is correct here. 2,2 vs 2,5,6. or 2,3 vs 2,2,6
-    },
     HandRank::Straight => {
       true
     },
     HandRank::Flush => {
       true
     },
-    HandRank::FullHouse => {
-      true
-    },
-    HandRank::FourOfAKind => {
-      true
-    },
     HandRank::StraightFlush => {
       true
     },
@@ -457,6 +462,7 @@ fn is_hand_part_of_made_up_hand(hand: &Vec<Card>, hand_rank: &HandRank, combinat
 }
 
 // returns tuple of: raw hand value, hand equity, type of hand
+#[allow(clippy::ptr_arg, clippy::needless_late_init)]
 fn get_best_hand(my_hand: &Vec<Card>, community: &Vec<Card>, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> (f32, f32, HandRank, Vec<Card>) {
   let mut sorted_cards = Vec::<Card>::new();
   for h in my_hand {
@@ -478,22 +484,24 @@ fn get_best_hand(my_hand: &Vec<Card>, community: &Vec<Card>, combinations: &Hash
     (highest_score, highest_eq)
     },
     6|7 => {
+      // board can play on its own. when several 5 card hands have the same value,
+      // keep the one that uses fewest hole cards, so assembled hand only has hole cards that actually matter.
       let possible_hands = sorted_cards.iter().combinations(5);
       let mut highest_score = 0.0;
       let mut highest_eq = 0.0;
+      let mut highest_num_hole_cards = 0;
       for hand in possible_hands {
         let mut new_hand = Vec::<Card>::new();
         for c in hand {
           new_hand.push(*c);
         }
         new_hand.sort();
-        if find_common_cards_in_pack_no_ref(my_hand, &new_hand) == 0 {
-          continue;
-        }
+        let num_hole_cards = find_common_cards_in_pack_no_ref(my_hand, &new_hand);
         let (score, eq) = combinations[&new_hand];
-        if score > highest_score {
+        if score > highest_score || (score == highest_score && num_hole_cards < highest_num_hole_cards) {
           highest_score = score;
           highest_eq = eq;
+          highest_num_hole_cards = num_hole_cards;
           assembled_hand = new_hand.clone();
         }
       }
@@ -504,6 +512,7 @@ fn get_best_hand(my_hand: &Vec<Card>, community: &Vec<Card>, combinations: &Hash
   return (highest_value, equity, get_best_hand_string(highest_value), assembled_hand)
 }
 
+#[allow(clippy::ptr_arg)]
 fn is_early_position_range(hand: &Vec<Card>) -> bool {
   // hand is sorted from smaller card first, to larger card last
 
@@ -821,7 +830,54 @@ fn is_sb_position_range(hand: &Vec<Card>) -> bool {
   return false
 }
 
-fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &str, card_deck: &Vec<Card>, starting_hands: &HashMap<Vec<Card>, (f32,f32,f32)>, combinations: &HashMap<Vec<Card>, (f32,f32)>, _simulated_hands: &HashMap::<Vec<Card>, (u64, u64, HashMap<HandRank, u64>, u64, u64, u64)>) {
+fn is_in_position_range(position: TablePosition, hand: &Vec<Card>) -> bool {
+  return match position {
+    TablePosition::Early => is_early_position_range(hand),
+    TablePosition::Mid => is_mid_position_range(hand),
+    TablePosition::Late => is_late_position_range(hand),
+    TablePosition::Button => is_btn_position_range(hand),
+    TablePosition::SB => is_sb_position_range(hand),
+  }
+}
+
+// position of a seat from input_pos (0 is hero) when the dealer is at 1 based dealer_pos, None is the big blind
+fn seat_position(seat: usize, dealer_pos: usize, num_seats: usize) -> Option<TablePosition> {
+  let seats_after_button = (seat + num_seats + 1 - dealer_pos) % num_seats;
+  return match seats_after_button {
+    0 => Some(TablePosition::Button),
+    1 => Some(TablePosition::SB),
+    2 => None,
+    n if num_seats - n == 1 => Some(TablePosition::Late),
+    n if num_seats - n == 2 => Some(TablePosition::Mid),
+    _ => Some(TablePosition::Early),
+  }
+}
+
+// data tables hand analysis looks hands up in, loaded once at start
+struct AnalysisTables<'a> {
+  card_deck: &'a Vec<Card>,
+  starting_hands: &'a HashMap<Vec<Card>, (f32,f32,f32)>,
+  combinations: &'a HashMap<Vec<Card>, (f32,f32)>,
+  simulated_hands: &'a SimulatedHands,
+  score_table: &'a score_table::ScoreTable,
+  range_model: &'a range_narrowing::RangeModel,
+}
+
+// optional inputs of hand analysis, each one turns on more output when it is there
+struct AnalysisOptions<'a> {
+  // opponents per seat, empty when they are any two cards
+  profiles: Vec<(usize, opponent_profile::OpponentProfile)>,
+  // seat, name and stats of opponents from the player stats db, they take the place of the seat's profile
+  player_stats: Vec<(usize, String, player_stats::PlayerStats)>,
+  stack_info: Option<push_fold::StackInfo>,
+  preflop_matrix: Option<&'a preflop_matrix::PreflopMatrix>,
+  tournament: Option<icm::TournamentContext>,
+  dead_cards: Vec<Card>,
+}
+
+// hold'em only, other games go through a Variant in their own modes
+#[allow(clippy::ptr_arg, clippy::type_complexity, clippy::partialeq_to_none, clippy::needless_range_loop, clippy::needless_borrow, clippy::println_empty_string)]
+fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &str, tables: &AnalysisTables, options: &AnalysisOptions) {
   let start_main_ts = Instant::now();
   let mut total_pot = 0.0;
   //let mut main_pot = 0.0;
@@ -928,10 +984,15 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
     println!("Detected duplicate card in input: {:?}", input_cards);
     return
   }
+  // burned or exposed cards can't be in anyone's hand or come on the board
+  if input_cards.iter().any(|c| options.dead_cards.contains(c)) {
+    println!("Dead cards overlap with input: {:?} vs {:?}", options.dead_cards, input_cards);
+    return
+  }
 
   if input_cards.len() == 2 {
     input_cards.sort();
-    let (_, avg_eq, _) = starting_hands[&input_cards];
+    let (_, avg_eq, _) = tables.starting_hands[&input_cards];
     /*let mut win_ch = 0.0;
     {
       let (num_won, num_total, _, _won_flop, _won_turn, _won_river) = simulated_hands[&input_cards];
@@ -939,20 +1000,36 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
       //println!("SimData: {:?} - win: {:.2}%, flop: {:.2}% turn: {:.2}% river: {:.2}%", input_cards, win_ch*100.0, (won_flop as f64/num_won as f64)*100.0, (won_turn as f64/num_won as f64)*100.0, (won_river as f64/num_won as f64)*100.0);
     }*/
 
-    let playable_range = match my_position {
-      TablePosition::Early => is_early_position_range(&input_cards),
-      TablePosition::Mid => is_mid_position_range(&input_cards),
-      TablePosition::Late => is_late_position_range(&input_cards),
-      TablePosition::Button => is_btn_position_range(&input_cards),
-      TablePosition::SB => is_sb_position_range(&input_cards),
-    };
+    let playable_range = is_in_position_range(my_position, &input_cards);
     let is_playable_str = match playable_range {
       true => "PLAYABLE",
       false => "FOLD",
     };
     println!("hand cards: {:?}, AvgEq: {:.2}%, Playable: {}", input_cards, avg_eq*100.0, is_playable_str);
+    // AvgEq is a lookup from the full deck, with dead cards equity is sampled again without them
+    if !options.dead_cards.is_empty() {
+      let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+      let eq = variant::equity(&variant::Holdem{score_table: tables.score_table}, &input_cards, &[], &options.dead_cards, 1, &mut rng);
+      println!("Dead cards: {:?}, Equity with them out of the deck: {:.2}%", options.dead_cards, eq*100.0);
+    }
     println!("Pot: ${:.2}, To Call: ${:.2}", total_pot, call_amount);
     println!("Position: {}", my_position);
+    if let Some(stack_info) = options.stack_info {
+      let config = stack_info.push_fold_config(dealer_pos_arr.len());
+      if config.stack_bb < push_fold::HINT_THRESHOLD_BB {
+        match options.preflop_matrix {
+          Some(matrix) => {
+            // seats after the button, then order of preflop action where the big blind is last
+            let n = dealer_pos_arr.len();
+            let seats_after_button = (n + 1 - dealer_pos) % n;
+            let action_pos = (seats_after_button + n - 3) % n;
+            let facing_shove = call_amount > stack_info.big_blind;
+            println!("{}", push_fold::push_fold_hint(matrix, config, action_pos, preflop_matrix::starting_hand_class(&input_cards), facing_shove));
+          },
+          None => println!("Push/fold hint needs the preflop matrix, run: poker_ev preflop_matrix"),
+        }
+      }
+    }
     return
   }
   if input_cards.len() < 5 {
@@ -971,19 +1048,23 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
 
   println!("hand cards: {:?}", hand);
   println!("community cards: {:?}", community);
+  let texture = board_texture::analyze_board(&community);
+  println!("Board: {}", texture);
+  println!("Board JSON: {}", texture.to_json());
   println!("Pot: ${:.2}, To Call: ${:.2}", total_pot, call_amount);
   //println!("Position: {}", my_position);  
+  if !options.dead_cards.is_empty() {
+    println!("Dead cards: {:?}", options.dead_cards);
+  }
+  let live_deck: Vec<Card> = tables.card_deck.iter().filter(|c| !options.dead_cards.contains(c)).cloned().collect();
 
   let mut all_cards = Vec::<Card>::new();
   all_cards.extend(hand.to_vec().iter());
   all_cards.extend(community.to_vec().iter());
 
-  let (flop_hand_type, real_my_hand_eq, improved_hands_hash_map, opponent_hands_hash_map, opponent_num_hands, rel_my_hand_eq) 
-    = get_hand_equity_and_opponent_range(&hand, &community, &combinations, &starting_hands, &card_deck);
+  let (flop_hand_type, real_my_hand_eq, _improved_hands_hash_map, opponent_hands_hash_map, opponent_num_hands) 
+    = get_hand_equity_and_opponent_range(&hand, &community, tables.combinations, tables.starting_hands, &live_deck);
   
-  let num_opponents = 1; // todo: add support for how many opponents are active on the table
-  let num_cards_in_deck_left = (card_deck.len()-community.len()-hand.len()) as i32 - num_opponents*2;
-
   //println!("Oppont: {:.2}%", oppon_eq*100.0);
   //println!("{:.3}-{:.3}", min_eq, max_eq);
   println!("Opponent hand range:");
@@ -993,6 +1074,7 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
     let s = hand_type.to_string();
     println!("{:<20}:{:.1}%", s, (opponent_hands_hash_map[&hand_type] as f32/opponent_num_hands as f32)*100.0);
   }
+  print!("{}", blockers::analyze_blockers(&hand, &community, &live_deck, tables.score_table));
   println!("");
 
   // show my hands relative strength to any opponent's hand. essentially it is my equity
@@ -1003,19 +1085,85 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
     //println!("SimData: {:?} - win: {:.2}%, flop: {:.2}% turn: {:.2}% river: {:.2}%", hand, win_ch*100.0, (won_flop as f64/num_won as f64)*100.0, (won_turn as f64/num_won as f64)*100.0, (won_river as f64/num_won as f64)*100.0);
   }*/
 
-  println!("Hand Equity: {:.2}%, Type: {} ({:.2}%)", real_my_hand_eq*100.0, flop_hand_type, rel_my_hand_eq*100.0);
+  // exact position among all opponent combos instead of equity normalised within the hand class
+  let nut_analysis = nut_rank::analyze_nut_rank(&hand, &community, &live_deck, nut_rank::TOP_HANDS, tables.score_table);
+  println!("Hand Equity: {:.2}%, Type: {}", real_my_hand_eq*100.0, flop_hand_type);
+  print!("{}", nut_analysis);
+  // equity against every opponent combo separately, so it's clear what part of the range hero beats
+  let vs_any = range_equity::hand_vs_range(&hand, &preflop_matrix::PreflopRange::any(), &community, &options.dead_cards, tables.score_table);
+  println!("Equity vs any two cards: {:.2}%", vs_any.hero.equity*100.0);
+  print!("{}", vs_any.hero_vs_combos());
+  println!("{}", contribution::analyze_contribution(&hand, &community, &live_deck, tables.combinations, tables.score_table));
+
+  // with opponent profiles, equity is against their ranges instead of any two cards
+  let mut hand_eq = real_my_hand_eq;
+  // known players are read from their stats, every other seat from its profile
+  let mut opponents: Vec<(usize, String, opponent_profile::OpponentProfile, Option<&player_stats::PlayerStats>)> = options.profiles.iter()
+    .filter(|(seat, _)| !options.player_stats.iter().any(|(s, _, _)| s == seat))
+    .map(|(seat, profile)| (*seat, profile.to_string(), *profile, None))
+    .collect();
+  for (seat, name, stats) in &options.player_stats {
+    println!("{}: {}", name, stats);
+    opponents.push((*seat, name.clone(), opponent_profile::OpponentProfile::from_stats(stats), Some(stats)));
+  }
+  opponents.sort_by_key(|o| o.0);
+  if !opponents.is_empty() {
+    let mut seen_cards = hand.clone();
+    seen_cards.extend(community.iter());
+    seen_cards.extend(options.dead_cards.iter());
+    // total pot already has the bet in it. we don't know which opponent made it, so every range is narrowed as a bettor
+    let bet_ratio = match call_amount > 0.0 {
+      true if total_pot > call_amount => Some(call_amount/(total_pot - call_amount)),
+      true => Some(1.0),
+      false => None,
+    };
+    let mut ranges = Vec::new();
+    for (seat, _, profile, stats) in &opponents {
+      let position = seat_position(*seat, dealer_pos, dealer_pos_arr.len());
+      let t = match stats {
+        Some(stats) => range_narrowing::tendencies(position, Some(stats)),
+        None => range_narrowing::at_position(&profile.tendencies(), position),
+      };
+      ranges.push(tables.range_model.live_range(&t, &community, &seen_cards, bet_ratio, tables.score_table));
+    }
+    // all opponents are dealt together, hero has to beat every one of them on the same board
+    let eq_vs_profiles = tables.range_model.equity_vs_ranges(&hand, &community, &options.dead_cards, &ranges, tables.score_table);
+    let names: Vec<String> = opponents.iter().map(|(_, name, _, _)| name.clone()).collect();
+    println!("Opponent profiles: {}", names.join(", "));
+    println!("Equity vs profiles: {:.2}%", eq_vs_profiles*100.0);
+    hand_eq = eq_vs_profiles;
+  }
+  // in tournaments chips aren't worth the same, so prize equity change is shown next to chip EV
+  let dollar_ev_str = |dollar_ev: Option<f32>| -> String {
+    return match dollar_ev {
+      Some(v) => format!(" ($EV: {:+.2})", v),
+      None => String::new(),
+    }
+  };
+  let print_raise_ev = |amount: f32| {
+    let ev = calculate_ev(total_pot, amount, hand_eq);
+    if !opponents.is_empty() {
+      // every opponent has to fold for us to take the pot right away
+      let fold_ch: f32 = opponents.iter().map(|(_, _, p, _)| p.fold_probability(amount/total_pot)).product();
+      let fe_ev = calculate_ev_with_fold_equity(total_pot, amount, hand_eq, fold_ch);
+      let dollar_ev = options.tournament.as_ref().map(|t| t.raise_dollar_ev(total_pot, amount, hand_eq, fold_ch));
+      println!("RAISE ${:.2}: {:+.2} (fold equity {:.1}%: {:+.2}){}", amount, ev, fold_ch*100.0, fe_ev, dollar_ev_str(dollar_ev));
+    } else {
+      let dollar_ev = options.tournament.as_ref().map(|t| t.raise_dollar_ev(total_pot, amount, hand_eq, 0.0));
+      println!("RAISE ${:.2}: {:+.2}{}", amount, ev, dollar_ev_str(dollar_ev));
+    }
+  };
+
   println!("EV:");
   if call_amount > 0.0 {
-    let ev = calculate_ev(total_pot, call_amount, real_my_hand_eq);
-    println!("CALL  ${:.2}: {:+.2}", call_amount, ev);
-    let ev = calculate_ev(total_pot, call_amount*2.0, real_my_hand_eq);
-    println!("RAISE ${:.2}: {:+.2}", call_amount*2.0, ev);
+    let ev = calculate_ev(total_pot, call_amount, hand_eq);
+    let dollar_ev = options.tournament.as_ref().map(|t| t.call_dollar_ev(total_pot, call_amount, hand_eq));
+    println!("CALL  ${:.2}: {:+.2}{}", call_amount, ev, dollar_ev_str(dollar_ev));
+    print_raise_ev(call_amount*2.0);
   } else {
     // for limit $1\2 table:
-    let ev = calculate_ev(total_pot, 1.0, real_my_hand_eq);
-    println!("RAISE ${:.2}: {:+.2}", 1.0, ev);
-    let ev = calculate_ev(total_pot, 2.0, real_my_hand_eq);
-    println!("RAISE ${:.2}: {:+.2}", 2.0, ev);
+    print_raise_ev(1.0);
+    print_raise_ev(2.0);
     /*let ev = calculate_ev(total_pot, total_pot*0.5, real_my_hand_eq);
     println!("RAISE(1/2) ${:.2}: {:+.2}", total_pot*0.5, ev);
     let ev = calculate_ev(total_pot, total_pot*0.75, real_my_hand_eq);
@@ -1023,23 +1171,44 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
     let ev = calculate_ev(total_pot, total_pot, real_my_hand_eq);
     println!("RAISE(pot) ${:.2}: {:+.2}", total_pot, ev);*/
   }
-  let mut sorted_keys: Vec<&HandRank> = improved_hands_hash_map.keys().collect();
-  sorted_keys.sort();
-  if sorted_keys.len() > 0 {
-    println!("Drawing hands:");
+  if community.len() < 5 {
+    // exact odds over every turn and river card left, including runner-runner hands on the flop
+    let runout_odds = runouts::calculate_runout_odds(&hand, &community, &live_deck, tables.combinations, tables.score_table);
+    let improved_ranks = runout_odds.improved_ranks();
+    if !improved_ranks.is_empty() {
+      println!("Drawing hands (by river):");
+    }
+    for hand_type in improved_ranks {
+      let s = hand_type.to_string();
+      let perc = runout_odds.probability(&hand_type);
+      let perc_behind = runout_odds.probability_behind(&hand_type);
+      if call_amount == 0.0 {
+        let ev = calculate_ev(total_pot, total_pot*0.5, perc);
+        println!("{:<20}:{:.1}% (behind {:.1}%)  RAISE(1/2) EV: {:+.2}", s, perc*100.0, perc_behind*100.0, ev);
+      } else {
+        let ev = calculate_ev(total_pot, call_amount, perc);
+        println!("{:<20}:{:.1}% (behind {:.1}%)  CALL EV: {:+.2}", s, perc*100.0, perc_behind*100.0, ev);
+      }
+    }
+    let win_ch = runout_odds.improve_and_win_probability();
+    let lose_ch = runout_odds.improve_and_lose_probability();
+    if call_amount > 0.0 && win_ch > 0.0 {
+      let implied = runouts::implied_odds_needed(total_pot, call_amount, win_ch);
+      if implied > 0.0 {
+        println!("Implied odds: need ${:.2} more when we hit", implied);
+      } else {
+        println!("Implied odds: pot odds are enough");
+      }
+    }
+    if lose_ch > 0.0 {
+      // assume we pay off one pot sized bet when we hit and are still behind
+      println!("Reverse implied odds: -${:.2} ({:.1}% to hit and lose)", runouts::reverse_implied_odds(total_pot, lose_ch), lose_ch*100.0);
+    }
   }
-  for hand_type in sorted_keys {
-    let s = hand_type.to_string();
-    let num_outs = improved_hands_hash_map[hand_type];
-    let perc = num_outs as f32/num_cards_in_deck_left as f32;
-    //println!("{}: {}/{} = {:.2}", hand_type, num_outs, num_cards_in_deck_left, perc);
-    //let new_total_pot = total_pot+call_amount; // this is if I add my call to the pot
-    if call_amount == 0.0 {
-      let ev = calculate_ev(total_pot, total_pot*0.5, perc);
-      println!("{:<20}:{:.1}%  RAISE(1/2) EV: {:+.2}", s, perc*100.0, ev);
-    } else {
-      let ev = calculate_ev(total_pot, call_amount, perc);
-      println!("{:<20}:{:.1}%  CALL EV: {:+.2}", s, perc*100.0, ev);
+  if community.len() < 5 {
+    let draw_analysis = draws::analyze_draws(&hand, &community, &live_deck, tables.score_table);
+    if !draw_analysis.draws.is_empty() {
+      println!("{}", draw_analysis);
     }
   }
 
@@ -1050,7 +1219,8 @@ fn calculcate_hand_ev(input: &str, pot_str: &str, action_str: &str, pos_str: &st
 fn main() -> Result<(), Error> {
   //let start_init_ts = Instant::now();
   let combinations_path: String =   "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/combinations.bin".to_string();
-  let starting_hands_path: String = "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/starting_hands.bin".to_string();
+  // v2: generated with is_hand_part_of_made_up_hand counting two pair with a board pair, older files are ignored
+  let starting_hands_path: String = "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/starting_hands_v2.bin".to_string();
   let simulated_hands_path: String = "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/simulated_hands.bin".to_string();
   let hands_csv_path: String =      "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/hands.csv".to_string();
   let trigger_path: String =        "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/trigger".to_string();
@@ -1058,6 +1228,13 @@ fn main() -> Result<(), Error> {
   let input_pos_path: String =     "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_pos".to_string();
   let input_pot_path: String =      "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_pot".to_string();
   let input_action_path: String =      "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_action".to_string();
+  let input_profiles_path: String =    "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_profiles".to_string();
+  let input_stack_path: String =       "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_stack".to_string();
+  let input_tournament_path: String =  "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_tournament".to_string();
+  let input_dead_path: String =        "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_dead".to_string();
+  let input_players_path: String =     "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/input_players".to_string();
+  let preflop_matrix_path: String = "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/preflop_matrix.bin".to_string();
+  let player_stats_path: String =   "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/player_stats.db".to_string();
 
   let card_deck = conv_string_to_cards("2c 3c 4c 5c 6c 7c 8c 9c Tc Jc Qc Kc Ac 2h 3h 4h 5h 6h 7h 8h 9h Th Jh Qh Kh Ah 2s 3s 4s 5s 6s 7s 8s 9s Ts Js Qs Ks As 2d 3d 4d 5d 6d 7d 8d 9d Td Jd Qd Kd Ad");
 
@@ -1134,7 +1311,8 @@ fn main() -> Result<(), Error> {
     simulated_hands = bincode::deserialize_from(f).unwrap();
   } else {
     println!("Generating simulated hands...");
-    simulate_game(10, 10000000, 6, &mut simulated_hands, &combinations, &card_deck);
+    let score_table = score_table::ScoreTable::new(&combinations);
+    simulate_game(10, 10000000, 6, &mut simulated_hands, &variant::Holdem{score_table: &score_table});
 
     let mut f = BufWriter::new(File::create(simulated_hands_path).unwrap());
     bincode::serialize_into(&mut f, &simulated_hands).unwrap();
@@ -1162,6 +1340,9 @@ fn main() -> Result<(), Error> {
   //let duration_init = start_init_ts.elapsed();
   //println!("Init duration is: {:?}", duration_init);
 
+  let score_table = score_table::ScoreTable::new(&combinations);
+  let range_model = range_narrowing::RangeModel::new(&card_deck, &starting_hands);
+
   let args: Vec<String> = env::args().collect();
   if args.len() < 2 {
     println!("Usage: poker_ev mode input_hand pot");
@@ -1170,24 +1351,115 @@ fn main() -> Result<(), Error> {
   let mode: &str = &(args[1]);
   match mode {
     "once" => {
-      if args.len() != 6 {
-        panic!("Not enough arguments provided. Expecting 4, got: {}", args.len()-2);
+      if args.len() < 6 || args.len() > 11 {
+        panic!("Not enough arguments provided. Expecting 4 to 9, got: {}", args.len()-2);
       }
       // example hand input: "C8 H5 H7 D12 D6"
       // example put input: "Total pot: $1.30\nMain pot: $1.10\n\n"
-      calculcate_hand_ev(&(args[2]), &(args[3].to_lowercase()), &(args[4].to_lowercase()), &(args[5]), &card_deck, &starting_hands, &combinations, &simulated_hands);
+      // optional arguments can be left empty ("") to get to the ones after them
+      let optional_arg = |i: usize| args.get(i).filter(|s| !s.trim().is_empty());
+      // optional opponent profiles per seat, same order as position input: "hero - tag nit - station"
+      let profiles = match optional_arg(6) {
+        Some(s) => opponent_profile::parse_seat_profiles(s, args[5].split(' ').count())?,
+        None => Vec::new(),
+      };
+      // optional hero's stack, big blind and ante for the push/fold hint: "$12.40 $1 $0.10"
+      let stack_info = match optional_arg(7) {
+        Some(s) => Some(s.parse::<push_fold::StackInfo>()?),
+        None => None,
+      };
+      // optional tournament payouts and stacks for $EV: "50,30,20/1500,2200,800,3000"
+      let tournament = match optional_arg(8) {
+        Some(s) => Some(s.parse::<icm::TournamentContext>()?),
+        None => None,
+      };
+      // optional dead cards, burned or exposed: "Kd 7s"
+      let dead_cards = match optional_arg(9) {
+        Some(s) => conv_string_to_cards(s.trim()),
+        None => Vec::new(),
+      };
+      // optional player names per seat, same order as position input, opponents with stats in the db are read from them: "hero - villain42 - - fish99"
+      let player_stats = match optional_arg(10) {
+        Some(s) => player_stats::seat_stats(s, args[5].split(' ').count(), &player_stats::PlayerStatsDb::open(&player_stats_path)?)?,
+        None => Vec::new(),
+      };
+      let preflop_matrix = load_preflop_matrix_if_exists(&preflop_matrix_path, &combinations);
+      let tables = AnalysisTables{card_deck: &card_deck, starting_hands: &starting_hands, combinations: &combinations, simulated_hands: &simulated_hands, score_table: &score_table, range_model: &range_model};
+      let options = AnalysisOptions{profiles: profiles, player_stats: player_stats, stack_info: stack_info, preflop_matrix: preflop_matrix.as_ref(), tournament: tournament, dead_cards: dead_cards};
+      calculcate_hand_ev(&(args[2]), &(args[3].to_lowercase()), &(args[4].to_lowercase()), &(args[5]), &tables, &options);
     },
     "loop" => {
       let trigger_path_file = Path::new(&trigger_path);
+      let preflop_matrix = load_preflop_matrix_if_exists(&preflop_matrix_path, &combinations);
+      let tables = AnalysisTables{card_deck: &card_deck, starting_hands: &starting_hands, combinations: &combinations, simulated_hands: &simulated_hands, score_table: &score_table, range_model: &range_model};
+      // the db is opened once, stats are read from it on every hand so imports between hands are picked up
+      let stats_db = match Path::new(&player_stats_path).exists() {
+        true => Some(player_stats::PlayerStatsDb::open(&player_stats_path)?),
+        false => None,
+      };
       loop {
         if trigger_path_file.exists() {
           let input_hand = fs::read_to_string(Path::new(&input_hand_path)).unwrap().trim().to_string();
           let input_pos = fs::read_to_string(Path::new(&input_pos_path)).unwrap().trim().to_string();
           let input_pot = fs::read_to_string(Path::new(&input_pot_path)).unwrap().to_lowercase();
           let input_action = fs::read_to_string(Path::new(&input_action_path)).unwrap().trim().to_lowercase();
+          // profiles are optional, without them opponents are any two cards
+          let mut profiles = Vec::new();
+          if Path::new(&input_profiles_path).exists() {
+            let input_profiles = fs::read_to_string(Path::new(&input_profiles_path)).unwrap();
+            profiles = match opponent_profile::parse_seat_profiles(&input_profiles, input_pos.split(' ').count()) {
+              Ok(p) => p,
+              Err(e) => {
+                println!("Ignoring opponent profiles: {}", e);
+                Vec::new()
+              },
+            };
+          }
+          // stack is optional too, it turns on the push/fold hint when short
+          let mut stack_info = None;
+          if Path::new(&input_stack_path).exists() {
+            let input_stack = fs::read_to_string(Path::new(&input_stack_path)).unwrap();
+            stack_info = match input_stack.trim().parse::<push_fold::StackInfo>() {
+              Ok(s) => Some(s),
+              Err(e) => {
+                println!("Ignoring stack: {}", e);
+                None
+              },
+            };
+          }
+          let mut tournament = None;
+          if Path::new(&input_tournament_path).exists() {
+            let input_tournament = fs::read_to_string(Path::new(&input_tournament_path)).unwrap();
+            tournament = match input_tournament.parse::<icm::TournamentContext>() {
+              Ok(t) => Some(t),
+              Err(e) => {
+                println!("Ignoring tournament: {}", e);
+                None
+              },
+            };
+          }
+          let mut dead_cards = Vec::new();
+          if Path::new(&input_dead_path).exists() {
+            let input_dead = fs::read_to_string(Path::new(&input_dead_path)).unwrap();
+            if !input_dead.trim().is_empty() {
+              dead_cards = conv_string_to_cards(input_dead.trim());
+            }
+          }
+          let mut player_stats = Vec::new();
+          if let (Some(db), true) = (&stats_db, Path::new(&input_players_path).exists()) {
+            let input_players = fs::read_to_string(Path::new(&input_players_path)).unwrap();
+            player_stats = match player_stats::seat_stats(&input_players, input_pos.split(' ').count(), db) {
+              Ok(p) => p,
+              Err(e) => {
+                println!("Ignoring player stats: {}", e);
+                Vec::new()
+              },
+            };
+          }
           fs::remove_file(trigger_path_file).unwrap();
 
-          calculcate_hand_ev(&input_hand, &input_pot, &input_action, &input_pos, &card_deck, &starting_hands, &combinations, &simulated_hands);
+          let options = AnalysisOptions{profiles: profiles, player_stats: player_stats, stack_info: stack_info, preflop_matrix: preflop_matrix.as_ref(), tournament: tournament, dead_cards: dead_cards};
+          calculcate_hand_ev(&input_hand, &input_pot, &input_action, &input_pos, &tables, &options);
           println!("END");
         } else {
           let sleep_amount = Duration::from_millis(100);
@@ -1195,21 +1467,463 @@ fn main() -> Result<(), Error> {
         }        
       }
     }
+    "import" => {
+      if args.len() != 3 {
+        panic!("Not enough arguments provided. Expecting 1, got: {}", args.len()-2);
+      }
+      // file or directory with hand histories
+      let hands = hand_history::import_hand_histories(Path::new(&args[2]))?;
+      hand_history::print_import_summary(&hands);
+      let mut stats_db = player_stats::PlayerStatsDb::open(&player_stats_path)?;
+      let added = stats_db.add_hands(&hands)?;
+      println!("Added {} new hands to player stats ({} players)", added, stats_db.num_players()?);
+    },
+    "stats" => {
+      // all players, or a single one
+      let stats_db = player_stats::PlayerStatsDb::open(&player_stats_path)?;
+      player_stats::print_player_stats(&stats_db, args.get(2).map(|s| s.as_str()))?;
+    },
+    "review" => {
+      if args.len() != 3 {
+        panic!("Not enough arguments provided. Expecting 1, got: {}", args.len()-2);
+      }
+      let hands = hand_history::import_hand_histories(Path::new(&args[2]))?;
+      let stats_db = player_stats::PlayerStatsDb::open(&player_stats_path)?;
+      let ctx = review::ReviewContext{
+        combinations: &combinations,
+        starting_hands: &starting_hands,
+        card_deck: &card_deck,
+        stats_db: &stats_db,
+        range_model: &range_model,
+        score_table: &score_table,
+      };
+      println!("{}", review::review_session(&hands, &ctx)?);
+    },
+    "selfplay" => {
+      if args.len() < 5 {
+        panic!("Not enough arguments provided. Expecting number of hands and at least 2 bots, got: {}", args.len()-2);
+      }
+      // example: "100000" "chart" "equity" "potodds" "station" "random"
+      let num_hands: usize = args[2].parse().map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid number of hands: {}", args[2])))?;
+      let mut bots = Vec::new();
+      for s in &args[3..] {
+        bots.push(s.parse::<bots::BotKind>()?);
+      }
+      let ctx = bots::BotContext{combinations: &combinations, starting_hands: &starting_hands, card_deck: &card_deck, score_table: &score_table};
+      let config = game_engine::GameConfig{small_blind: 1, big_blind: 2, ante: 0};
+      let stack_bb = 100;
+      let results = self_play::run_self_play(&bots, num_hands, stack_bb, config, &ctx, &score_table);
+      self_play::print_self_play_results(&bots, &results, stack_bb);
+    },
+    "solve" => {
+      if args.len() < 7 || args.len() > 9 {
+        panic!("Not enough arguments provided. Expecting 5 to 7, got: {}", args.len()-2);
+      }
+      // example: "Ah 7d 2c Ks 9s" "QQ+,AK,KQs" "TT+,AQ+,KQ" 10 20 "0.33,0.75" 500
+      // flop spots need small ranges and one or two bet sizes, the tree has a branch for every turn and river card
+      let board = conv_string_to_cards(&args[2]);
+      if board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("board needs 3 to 5 cards, got: {}", board.len())))
+      }
+      let oop_range: preflop_matrix::PreflopRange = args[3].parse()?;
+      let ip_range: preflop_matrix::PreflopRange = args[4].parse()?;
+      let parse_amount = |v: &str| -> Result<f32, Error> {
+        return lexical::parse(v).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount '{}': {:?}", v, e)))
+      };
+      let pot = parse_amount(&args[5])?;
+      let stack = parse_amount(&args[6])?;
+      let bet_sizes = match args.get(7) {
+        Some(s) => s.split(',').map(|v| parse_amount(v.trim())).collect::<Result<Vec<f32>, Error>>()?,
+        None => vec![0.33, 0.75],
+      };
+      let iterations: usize = match args.get(8) {
+        Some(s) => s.parse().map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid number of iterations: {}", s)))?,
+        None => 500,
+      };
+      let config = solver::SolverConfig{pot: pot, effective_stack: stack, bet_sizes: bet_sizes.clone(), raise_sizes: bet_sizes, max_bets_per_street: 3};
+      solver::run_solver(&board, &oop_range, &ip_range, config, iterations, &score_table);
+    },
+    "pushfold" => {
+      if args.len() != 4 && args.len() != 5 {
+        panic!("Not enough arguments provided. Expecting 2 or 3, got: {}", args.len()-2);
+      }
+      // example: "6" "10" "0.1" for 6 players 10bb deep with 0.1bb ante, or "6" "chart" for the deepest stack to shove every hand
+      let num_players: usize = args[2].parse().map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid number of players: {}", args[2])))?;
+      if !(2..=10).contains(&num_players) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("push/fold needs 2 to 10 players, got: {}", num_players)))
+      }
+      let parse_bb = |v: &str| -> Result<f32, Error> {
+        return lexical::parse(v).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount of big blinds '{}': {:?}", v, e)))
+      };
+      let ante_bb = match args.get(4) {
+        Some(s) => parse_bb(s)?,
+        None => 0.0,
+      };
+      // arguments are checked before the matrix is loaded, generating it takes hours
+      let stack_bb = match args[3].as_str() {
+        "chart" => None,
+        v => Some(parse_bb(v)?),
+      };
+      let matrix = preflop_matrix::load_or_generate_preflop_matrix(&preflop_matrix_path, &combinations);
+      match stack_bb {
+        None => {
+          let chart = push_fold::push_fold_chart(&matrix, num_players, ante_bb);
+          push_fold::print_push_fold_chart(&chart, num_players, ante_bb);
+        },
+        Some(stack_bb) => {
+          let config = push_fold::PushFoldConfig{num_players: num_players, stack_bb: stack_bb, ante_bb: ante_bb};
+          push_fold::print_push_fold_solution(&push_fold::solve_push_fold(&matrix, config));
+        },
+      }
+    },
+    "icm" => {
+      if args.len() != 3 {
+        panic!("Not enough arguments provided. Expecting 1, got: {}", args.len()-2);
+      }
+      // payouts/stacks with optional future game simulation: "50,30,20/1500,2200,800,3000/100:20"
+      let context = args[2].parse::<icm::TournamentContext>()?;
+      icm::print_icm(&context);
+    },
+    "omaha" | "omaha8" => {
+      if args.len() < 5 || args.len() > 8 {
+        panic!("Not enough arguments provided. Expecting 3 to 6, got: {}", args.len()-2);
+      }
+      // example: "Ah Kh Qd Jd" "Th 9c 2s" "12.50" ["4.00"] [2] ["Kd 7s"]. 4 hole cards is PLO4, 5 is PLO5. omaha8 is hi/lo 8 or better.
+      // last argument is dead cards
+      let hole = conv_string_to_cards(&args[2]);
+      let board = conv_string_to_cards(&args[3]);
+      let dead = match args.get(7) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      if hole.len() != 4 && hole.len() != 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("PLO needs 4 or 5 hole cards, got: {}", hole.len())))
+      }
+      if board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("board needs 3 to 5 cards, got: {}", board.len())))
+      }
+      let mut seen_cards = hole.clone();
+      seen_cards.extend(board.iter());
+      seen_cards.extend(dead.iter());
+      seen_cards.sort();
+      if seen_cards.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("duplicate card in hole cards, board and dead cards: {:?} {:?} {:?}", hole, board, dead)))
+      }
+      let parse_amount = |v: &str| -> Result<f32, Error> {
+        return lexical::parse(v.replace(['$', ','], "")).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount '{}': {:?}", v, e)))
+      };
+      let total_pot = parse_amount(&args[4])?;
+      let call_amount = match args.get(5) {
+        Some(s) => parse_amount(s)?,
+        None => 0.0,
+      };
+      let num_opponents: usize = match args.get(6) {
+        Some(s) => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of opponents '{}': {}", s, e)))?,
+        None => 1,
+      };
+      if num_opponents == 0 {
+        return Err(Error::new(ErrorKind::InvalidInput, "need at least one opponent"))
+      }
+      // every player's hole cards and a full board have to come from one deck
+      if (num_opponents+1)*hole.len() + 5 + dead.len() > score_table::NUM_CARDS {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} players with {} hole cards, a board and {} dead cards need more than {} cards", num_opponents+1, hole.len(), dead.len(), score_table::NUM_CARDS)))
+      }
+      let analysis = omaha::analyze_omaha(&hole, &board, &dead, num_opponents, mode == "omaha8", &score_table);
+      print!("{}", analysis);
+      println!("Pot: ${:.2}, To Call: ${:.2}", total_pot, call_amount);
+      println!("EV:");
+      if call_amount > 0.0 {
+        println!("CALL  ${:.2}: {:+.2}", call_amount, calculate_ev(total_pot, call_amount, analysis.equity));
+      }
+      // pot limit, so pot is the biggest bet
+      let pot_bet = omaha::pot_limit_max_bet(total_pot, call_amount);
+      let half_pot_bet = call_amount + (total_pot + call_amount)*0.5;
+      println!("RAISE(1/2) ${:.2}: {:+.2}", half_pot_bet, calculate_ev(total_pot, half_pot_bet, analysis.equity));
+      println!("RAISE(pot) ${:.2}: {:+.2}", pot_bet, calculate_ev(total_pot, pot_bet, analysis.equity));
+    },
+    "shortdeck" => {
+      if args.len() < 5 || args.len() > 8 {
+        panic!("Not enough arguments provided. Expecting 3 to 6, got: {}", args.len()-2);
+      }
+      // example: "Ah Kd" "9h 8c 6s" "12.50" ["4.00"] [2] ["Kc 7s"]. board can be empty ("") for preflop, last argument is dead cards
+      let hand = conv_string_to_cards(&args[2]);
+      let board = if args[3].trim().is_empty() { Vec::new() } else { conv_string_to_cards(&args[3]) };
+      let dead = match args.get(7) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      if hand.len() != 2 || board.len() > 5 || board.len() == 1 || board.len() == 2 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 2 hole cards and 0, 3, 4 or 5 board cards, got: {} and {}", hand.len(), board.len())))
+      }
+      if hand.iter().chain(board.iter()).chain(dead.iter()).any(|c| c.rank < 6) {
+        return Err(Error::new(ErrorKind::InvalidInput, "short deck has no cards below six"))
+      }
+      let mut seen_cards = hand.clone();
+      seen_cards.extend(board.iter());
+      seen_cards.extend(dead.iter());
+      seen_cards.sort();
+      if seen_cards.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("duplicate card in hand, board and dead cards: {:?} {:?} {:?}", hand, board, dead)))
+      }
+      let parse_amount = |v: &str| -> Result<f32, Error> {
+        return lexical::parse(v.replace(['$', ','], "")).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount '{}': {:?}", v, e)))
+      };
+      let total_pot = parse_amount(&args[4])?;
+      let call_amount = match args.get(5) {
+        Some(s) => parse_amount(s)?,
+        None => 0.0,
+      };
+      let num_opponents: usize = match args.get(6) {
+        Some(s) => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of opponents '{}': {}", s, e)))?,
+        None => 1,
+      };
+      if num_opponents == 0 {
+        return Err(Error::new(ErrorKind::InvalidInput, "need at least one opponent"))
+      }
+      let deck_size = variant::Variant::deck(&short_deck::ShortDeck{}).len();
+      if (num_opponents+1)*2 + 5 + dead.len() > deck_size {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} players, a board and {} dead cards need more than {} cards", num_opponents+1, dead.len(), deck_size)))
+      }
+      let analysis = variant::analyze_variant(&short_deck::ShortDeck{}, &hand, &board, &dead, num_opponents);
+      print!("{}", analysis);
+      println!("Pot: ${:.2}, To Call: ${:.2}", total_pot, call_amount);
+      println!("EV:");
+      if call_amount > 0.0 {
+        println!("CALL  ${:.2}: {:+.2}", call_amount, calculate_ev(total_pot, call_amount, analysis.equity));
+      }
+      // raises are call plus a share of the pot after calling, like omaha
+      let half_pot_bet = call_amount + (total_pot + call_amount)*0.5;
+      let pot_bet = call_amount + total_pot + call_amount;
+      println!("RAISE(1/2) ${:.2}: {:+.2}", half_pot_bet, calculate_ev(total_pot, half_pot_bet, analysis.equity));
+      println!("RAISE(pot) ${:.2}: {:+.2}", pot_bet, calculate_ev(total_pot, pot_bet, analysis.equity));
+    },
+    "lowhand" => {
+      if args.len() != 3 {
+        panic!("Not enough arguments provided. Expecting 1, got: {}", args.len()-2);
+      }
+      // example: "7h 5d 4c 3s 2h", 5 to 7 cards
+      let cards: Vec<u8> = conv_string_to_cards(&args[2]).iter().map(score_table::card_index).collect();
+      if cards.len() < 5 || cards.len() > 7 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 5 to 7 cards, got: {}", cards.len())))
+      }
+      lowball::print_low_hand(&cards);
+    },
+    "stud" | "stud8" => {
+      if args.len() < 4 || args.len() > 6 {
+        panic!("Not enough arguments provided. Expecting 2 to 4, got: {}", args.len()-2);
+      }
+      // example: "Ah Kh" "Qh 9h" "Td 7c|f:5s Jd|2c 2d" "Kh". hero's down and up cards, then up cards of every opponent
+      // separated by '|' with folded players marked "f:", then other dead cards. stud8 is hi/lo 8 or better
+      let mut opponents = Vec::new();
+      if args.len() > 4 {
+        for o in args[4].split('|').filter(|o| !o.trim().is_empty()) {
+          let (folded, up) = match o.trim().strip_prefix("f:") {
+            Some(up) => (true, up.trim()),
+            None => (false, o.trim()),
+          };
+          opponents.push(stud::StudPlayer{down: Vec::new(), up: conv_string_to_cards(up), folded: folded});
+        }
+      }
+      let dead = if args.len() > 5 && !args[5].trim().is_empty() { conv_string_to_cards(&args[5]) } else { Vec::new() };
+      let state = stud::StudState{
+        hero: stud::StudPlayer{down: conv_string_to_cards(&args[2]), up: conv_string_to_cards(&args[3]), folded: false},
+        opponents: opponents,
+        dead: dead,
+        hi_lo: mode == "stud8",
+      };
+      state.validate()?;
+      print!("{}", stud::analyze_stud(&state, &score_table));
+    },
+    "variant" => {
+      if args.len() < 5 || args.len() > 7 {
+        panic!("Not enough arguments provided. Expecting 3 to 5, got: {}", args.len()-2);
+      }
+      // example: plo5 "Ah Kh Qd Jd 9s" "Th 9c 2s" [2] ["Kd 7s"]. variants: holdem, shortdeck, plo4, plo5, plo8, stud, stud8.
+      // stud takes hero's 3 to 7 cards and an empty board. last argument is dead cards
+      let variant = variant::variant_from_name(&args[2], &score_table)?;
+      let hole = conv_string_to_cards(&args[3]);
+      let board = if args[4].trim().is_empty() { Vec::new() } else { conv_string_to_cards(&args[4]) };
+      let deck = variant.deck();
+      let dead = match args.get(6) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      let mut seen_cards = hole.clone();
+      seen_cards.extend(board.iter());
+      seen_cards.sort();
+      if seen_cards.windows(2).any(|w| w[0] == w[1]) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("duplicate card in hole cards and board: {:?} {:?}", hole, board)))
+      }
+      if hole.iter().chain(board.iter()).any(|c| dead.contains(c)) {
+        return Err(Error::new(ErrorKind::InvalidInput, "dead cards overlap with hole or board cards"))
+      }
+      if hole.len() > variant.num_hole_cards() || board.len() > variant.board_size() || hole.iter().chain(board.iter()).any(|c| !deck.contains(c)) {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("cards don't fit {}: {} hole and {} board cards", variant.name(), hole.len(), board.len())))
+      }
+      let num_opponents: usize = match args.get(5) {
+        Some(s) if !s.trim().is_empty() => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of opponents '{}': {}", s, e)))?,
+        _ => 1,
+      };
+      if num_opponents == 0 {
+        return Err(Error::new(ErrorKind::InvalidInput, "need at least one opponent"))
+      }
+      // everyone's hole cards and the whole board come out of what is left after dead cards
+      if (num_opponents+1)*variant.num_hole_cards() + variant.board_size() + dead.len() > deck.len() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} can't be dealt to {} players with {} dead cards", variant.name(), num_opponents+1, dead.len())))
+      }
+      print!("{}", variant::analyze_variant(variant.as_ref(), &hole, &board, &dead, num_opponents));
+    },
+    "simulate" => {
+      if args.len() < 3 || args.len() > 5 {
+        panic!("Not enough arguments provided. Expecting 1 to 3, got: {}", args.len()-2);
+      }
+      // example: plo5 [100000] [6]. same variants as in "variant" mode
+      let variant = variant::variant_from_name(&args[2], &score_table)?;
+      let num_runs: u64 = match args.get(3) {
+        Some(s) => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of hands '{}': {}", s, e)))?,
+        None => 100000,
+      };
+      let num_players: usize = match args.get(4) {
+        Some(s) => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of players '{}': {}", s, e)))?,
+        None => 6,
+      };
+      if num_players < 2 || num_players*variant.num_hole_cards() + variant.board_size() > variant.deck().len() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{} can't be dealt to {} players", variant.name(), num_players)))
+      }
+      let mut variant_hands = SimulatedHands::new();
+      simulate_game(1, num_runs, num_players, &mut variant_hands, variant.as_ref());
+      print_simulated_hands(variant.as_ref(), &variant_hands);
+    },
+    "preflop_matrix" => {
+      preflop_matrix::load_or_generate_preflop_matrix(&preflop_matrix_path, &combinations);
+      println!("Preflop matrix saved to: {}", preflop_matrix_path);
+    },
+    "preflop_equity" => {
+      if args.len() != 4 {
+        panic!("Not enough arguments provided. Expecting 2, got: {}", args.len()-2);
+      }
+      // example: "AKo" "QQ", or ranges: "QQ+,AKs" "any"
+      let matrix = preflop_matrix::load_or_generate_preflop_matrix(&preflop_matrix_path, &combinations);
+      preflop_matrix::print_preflop_equity(&matrix, &(args[2]), &(args[3]))?;
+    },
+    "range_equity" => {
+      if args.len() < 5 || args.len() > 6 {
+        panic!("Not enough arguments provided. Expecting 3 or 4, got: {}", args.len()-2);
+      }
+      // example: "QQ+,AKs,AQs:0.5" "any" "Ks 7d 2c" ["Ah"]. ranges are the same as in preflop_equity, last argument is dead cards
+      let hero = args[2].parse::<preflop_matrix::PreflopRange>()?;
+      let villain = args[3].parse::<preflop_matrix::PreflopRange>()?;
+      let board = conv_string_to_cards(&args[4]);
+      if board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 3 to 5 board cards, got: {}", board.len())))
+      }
+      let dead = match args.get(5) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      print!("{}", range_equity::range_vs_range(&hero, &villain, &board, &dead, &score_table));
+    },
+    "equity_distribution" => {
+      if args.len() < 4 || args.len() > 6 {
+        panic!("Not enough arguments provided. Expecting 2 to 4, got: {}", args.len()-2);
+      }
+      // example: "Ah Kd" "Ks 7d 2c" ["TT+,AQ+,KQs"] ["Qh"]. opponent range is any two cards by default, last argument is dead cards
+      let hand = conv_string_to_cards(&args[2]);
+      let board = conv_string_to_cards(&args[3]);
+      if hand.len() != 2 || board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 2 hole cards and 3 to 5 board cards, got: {} and {}", hand.len(), board.len())))
+      }
+      let villain = match args.get(4) {
+        Some(s) if !s.trim().is_empty() => s.parse::<preflop_matrix::PreflopRange>()?,
+        _ => preflop_matrix::PreflopRange::any(),
+      };
+      let dead = match args.get(5) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      let result = range_equity::hand_vs_range(&hand, &villain, &board, &dead, &score_table);
+      println!("Equity: {:.2}% against {:.0} combos", result.hero.equity*100.0, result.villain.total_weight());
+      print!("{}", result.hero_vs_combos());
+    },
+    "board" => {
+      if args.len() != 3 {
+        panic!("Not enough arguments provided. Expecting 1, got: {}", args.len()-2);
+      }
+      // example: "Kh 7h 2h". texture of a 3 to 5 card board as JSON
+      let board = conv_string_to_cards(&args[2]);
+      if board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 3 to 5 board cards, got: {}", board.len())))
+      }
+      println!("{}", board_texture::analyze_board(&board).to_json());
+    },
+    "blockers" => {
+      if args.len() < 4 || args.len() > 5 {
+        panic!("Not enough arguments provided. Expecting 2 or 3, got: {}", args.len()-2);
+      }
+      // example: "Ah 5d" "Kh 7h 2h" ["Qs"]. last argument is dead cards
+      let hand = conv_string_to_cards(&args[2]);
+      let board = conv_string_to_cards(&args[3]);
+      if hand.len() != 2 || board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 2 hole cards and 3 to 5 board cards, got: {} and {}", hand.len(), board.len())))
+      }
+      let dead = match args.get(4) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      let live_deck: Vec<Card> = card_deck.iter().filter(|c| !dead.contains(c)).cloned().collect();
+      print!("{}", blockers::analyze_blockers(&hand, &board, &live_deck, &score_table));
+    },
+    "nutrank" => {
+      if args.len() < 4 || args.len() > 6 {
+        panic!("Not enough arguments provided. Expecting 2 to 4, got: {}", args.len()-2);
+      }
+      // example: "Ah 5d" "Kh 7h 2h" [10] ["Qs"]. number of hands that beat hero to list, then dead cards
+      let hand = conv_string_to_cards(&args[2]);
+      let board = conv_string_to_cards(&args[3]);
+      if hand.len() != 2 || board.len() < 3 || board.len() > 5 {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("expecting 2 hole cards and 3 to 5 board cards, got: {} and {}", hand.len(), board.len())))
+      }
+      let top_n: usize = match args.get(4) {
+        Some(s) => s.trim().parse().map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad number of hands '{}': {}", s, e)))?,
+        None => nut_rank::TOP_HANDS,
+      };
+      let dead = match args.get(5) {
+        Some(s) if !s.trim().is_empty() => conv_string_to_cards(s.trim()),
+        _ => Vec::new(),
+      };
+      let live_deck: Vec<Card> = card_deck.iter().filter(|c| !dead.contains(c)).cloned().collect();
+      print!("{}", nut_rank::analyze_nut_rank(&hand, &board, &live_deck, top_n, &score_table));
+    },
     _ => panic!("unknown mode: {}", mode),
   };
 
   Ok(())
 }
 
-fn simulate_game(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulated_hands: &mut HashMap::<Vec<Card>, (u64, u64, HashMap<HandRank, u64>, u64, u64, u64)>, combinations: &HashMap<Vec<Card>,(f32,f32)>, card_deck: &Vec<Card>) {
+// takes hours to generate, so analysis only uses the matrix when it has been generated before
+fn load_preflop_matrix_if_exists(path: &str, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> Option<preflop_matrix::PreflopMatrix> {
+  if !Path::new(path).exists() {
+    return None
+  }
+  return Some(preflop_matrix::load_or_generate_preflop_matrix(path, combinations))
+}
+
+// Deals num_pl hands of the variant to showdown, from its own deck, and counts wins, hand classes and the street
+// where the winner got ahead for every starting hand (sorted hole cards)
+#[allow(clippy::ptr_arg, clippy::type_complexity, clippy::manual_swap, clippy::needless_range_loop, clippy::vec_init_then_push, clippy::needless_borrow, clippy::bool_comparison, clippy::assign_op_pattern)]
+fn simulate_game(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulated_hands: &mut HashMap::<Vec<Card>, (u64, u64, HashMap<HandRank, u64>, u64, u64, u64)>, variant: &dyn variant::Variant) {
 
   let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
 
+  let card_deck = variant.deck();
   let num_cards_in_deck = card_deck.len() as u32;
+  let streets = variant.board_streets();
 
   for outter_run in 0..outter_runs {
     for sim_run_iter in 0..max_sim_runs {
-      println!("Running sim {}:{}/{}", outter_run+1, sim_run_iter+1, max_sim_runs);
+      if sim_run_iter % 100000 == 0 {
+        println!("Running sim {}:{}/{}", outter_run+1, sim_run_iter+1, max_sim_runs);
+      }
 
       let mut new_deck = card_deck.clone();
       // shuffle cards
@@ -1222,55 +1936,54 @@ fn simulate_game(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulated_h
 
       let mut players = Vec::<(Vec<Card>,(f32, HandRank, bool, bool, bool))>::new();
       players.resize(num_pl, (Vec::<Card>::new(),(0.0, HandRank::HighCard, false, false, false)));
-      for i in 0..num_pl {
-        players[i].0.push(new_deck.pop().unwrap());
+      for _ in 0..variant.num_hole_cards() {
+        for i in 0..num_pl {
+          players[i].0.push(new_deck.pop().unwrap());
+        }
       }
       for i in 0..num_pl {
-        players[i].0.push(new_deck.pop().unwrap());
         players[i].0.sort();
       }
       //println!("{:?}", players);
       let mut community_cards = Vec::<Card>::new();
-      community_cards.push(new_deck.pop().unwrap());
-      community_cards.push(new_deck.pop().unwrap());
-      community_cards.push(new_deck.pop().unwrap());
-      community_cards.push(new_deck.pop().unwrap());
-      community_cards.push(new_deck.pop().unwrap());
+      for _ in 0..variant.board_size() {
+        community_cards.push(new_deck.pop().unwrap());
+      }
       //println!("{:?}", community_cards);
       for i in 0..num_pl {
-        let (_, hand_equity, hand_rank, _) = get_best_hand(&players[i].0, &community_cards, &combinations);
-        players[i].1 = (hand_equity, hand_rank, false, false, false);
+        let hand_equity = variant.score(&players[i].0, &community_cards);
+        players[i].1 = (hand_equity, variant.rank_of_score(hand_equity), false, false, false);
       }
       //println!("{:?}", players);
       players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
       //println!("{:?}", players);
-      //now test if hand was winning starting with the flop
-      let mut temp_players = players.clone();
-      let mut flop_only = community_cards.clone();
-      flop_only.pop();
-      flop_only.pop();
-      for i in 0..num_pl {
-        let (_, hand_equity, hand_rank, _) = get_best_hand(&players[i].0, &flop_only, &combinations);
-        temp_players[i].1 = (hand_equity, hand_rank, false, false, false);
-      }
-      temp_players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-      // now see if top player was top on the flop
-      if temp_players[0].0 == players[0].0 {
-        players[0].1.2 = true;
-      }
+      // stud has no board, so the winner can only be told at the end
+      if streets.len() == 3 {
+        //now test if hand was winning starting with the flop
+        let mut temp_players = players.clone();
+        let flop_only = community_cards[..streets[0]].to_vec();
+        for i in 0..num_pl {
+          let hand_equity = variant.score(&players[i].0, &flop_only);
+          temp_players[i].1 = (hand_equity, variant.rank_of_score(hand_equity), false, false, false);
+        }
+        temp_players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // now see if top player was top on the flop
+        if temp_players[0].0 == players[0].0 {
+          players[0].1.2 = true;
+        }
 
-      //now test if hand was winning starting with the turn
-      let mut temp_players = players.clone();
-      let mut flop_and_turn_only = community_cards.clone();
-      flop_and_turn_only.pop();
-      for i in 0..num_pl {
-        let (_, hand_equity, hand_rank, _) = get_best_hand(&players[i].0, &flop_and_turn_only, &combinations);
-        temp_players[i].1 = (hand_equity, hand_rank, false, false, false);
-      }
-      temp_players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-      // now see if top player was top on the turn and was not already at the top on flop
-      if temp_players[0].0 == players[0].0 && players[0].1.2 == false {
-        players[0].1.3 = true;
+        //now test if hand was winning starting with the turn
+        let mut temp_players = players.clone();
+        let flop_and_turn_only = community_cards[..streets[0]+streets[1]].to_vec();
+        for i in 0..num_pl {
+          let hand_equity = variant.score(&players[i].0, &flop_and_turn_only);
+          temp_players[i].1 = (hand_equity, variant.rank_of_score(hand_equity), false, false, false);
+        }
+        temp_players.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // now see if top player was top on the turn and was not already at the top on flop
+        if temp_players[0].0 == players[0].0 && players[0].1.2 == false {
+          players[0].1.3 = true;
+        }
       }
 
       // and now test if top player become top only on the river
@@ -1314,6 +2027,39 @@ fn simulate_game(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulated_h
   }
 }
 
+// starting hand to (games won, games played, hand classes at showdown, won ahead from the flop, turn, river)
+type SimulatedHands = HashMap::<Vec<Card>, (u64, u64, HashMap<HandRank, u64>, u64, u64, u64)>;
+
+// hand classes players end up with at showdown in the variant's order, on which street the winner got ahead,
+// and the starting hands that won the most when there are few enough of them to see each one many times
+fn print_simulated_hands(variant: &dyn variant::Variant, simulated_hands: &SimulatedHands) {
+  let num_games: u64 = simulated_hands.values().map(|v| v.1).sum();
+  let num_won: u64 = simulated_hands.values().map(|v| v.0).sum();
+  println!("{}: {} hands dealt, {} starting hands", variant.name(), num_games, simulated_hands.len());
+  println!("Hand class at showdown:");
+  for hand_type in variant.hand_order().iter().rev() {
+    let count: u64 = simulated_hands.values().map(|v| v.2.get(hand_type).cloned().unwrap_or(0)).sum();
+    if count > 0 {
+      println!("{:<20}:{:.2}%", hand_type.to_string(), count as f64/num_games as f64*100.0);
+    }
+  }
+  if variant.board_streets().len() == 3 {
+    let won_flop: u64 = simulated_hands.values().map(|v| v.3).sum();
+    let won_turn: u64 = simulated_hands.values().map(|v| v.4).sum();
+    let won_river: u64 = simulated_hands.values().map(|v| v.5).sum();
+    println!("Winner ahead from: flop {:.1}%, turn {:.1}%, river {:.1}%", won_flop as f64/num_won as f64*100.0, won_turn as f64/num_won as f64*100.0, won_river as f64/num_won as f64*100.0);
+  }
+  let min_games = 100;
+  let mut best: Vec<(&Vec<Card>, f64)> = simulated_hands.iter().filter(|(_, v)| v.1 >= min_games).map(|(h, v)| (h, v.0 as f64/v.1 as f64)).collect();
+  best.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+  if !best.is_empty() {
+    println!("Best starting hands (at least {} hands):", min_games);
+  }
+  for (hand, win_ch) in best.iter().take(10) {
+    println!("{:?}: {:.2}%", hand, win_ch*100.0);
+  }
+}
+
 fn convert_card_to_int(card: &Card) -> u32 {
   match card.suit {
     CardSuit::Heart => (card.rank as u32)-1, // [2..14] -> [1..13]
@@ -1323,6 +2069,7 @@ fn convert_card_to_int(card: &Card) -> u32 {
   }
 }
 
+#[allow(clippy::type_complexity, clippy::manual_swap, clippy::needless_range_loop, clippy::vec_init_then_push, clippy::needless_borrow, clippy::needless_late_init, clippy::needless_borrows_for_generic_args)]
 fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulated_hands: &HashMap::<Vec<Card>, (u64, u64, HashMap<HandRank, u64>, u64, u64, u64)>, combinations: &HashMap<Vec<Card>,(f32,f32)>, card_deck: &Vec<Card>, starting_hands: &HashMap<Vec<Card>, (f32,f32,f32)>) {
   let ml_data_path = "/home/pavel/nvme/GitHub/poker_ml/expected_value/data/ml_data.csv".to_string();
   let mut csv_writer = Writer::from_path(ml_data_path).unwrap();
@@ -1340,6 +2087,9 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
   csv_writer.write_field("flop3").unwrap();
   csv_writer.write_field("turn").unwrap();
   csv_writer.write_field("river").unwrap();
+  for h in board_texture::BoardTexture::ml_header() {
+    csv_writer.write_field(h).unwrap();
+  }
   csv_writer.write_field("win_chance").unwrap();
   csv_writer.write_field("won_on_flop").unwrap();
   csv_writer.write_field("won_on_turn").unwrap();
@@ -1401,6 +2151,9 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
       csv_writer.write_field("none").unwrap();
       csv_writer.write_field("none").unwrap();
       csv_writer.write_field("none").unwrap();
+      for _ in board_texture::BoardTexture::ml_header() {
+        csv_writer.write_field("none").unwrap();
+      }
       let (num_won, num_total, _, won_flop, won_turn, won_river) = simulated_hands[&players[my_player_idx].0];
       let win_ch = num_won as f64/num_total as f64;
       csv_writer.write_field(win_ch.to_string()).unwrap();
@@ -1423,6 +2176,9 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
       csv_writer.write_field(&community_cards[2].to_string()).unwrap();
       csv_writer.write_field("none").unwrap();
       csv_writer.write_field("none").unwrap();
+      for f in board_texture::analyze_board(&community_cards[..3].to_vec()).ml_features() {
+        csv_writer.write_field(f).unwrap();
+      }
       csv_writer.write_field(win_ch.to_string()).unwrap();
       csv_writer.write_field(won_on_flop.to_string()).unwrap();
       csv_writer.write_field(won_on_turn.to_string()).unwrap();
@@ -1430,7 +2186,7 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
       let mut flop_cards = community_cards.clone();
       flop_cards.pop();
       flop_cards.pop();
-      let (_, real_my_hand_eq, _, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &flop_cards, &combinations, &starting_hands, &card_deck);
+      let (_, real_my_hand_eq, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &flop_cards, &combinations, &starting_hands, &card_deck);
       csv_writer.write_field(real_my_hand_eq.to_string()).unwrap();
       csv_writer.write_field(my_player_won.to_string()).unwrap();
       csv_writer.write_record(None::<&[u8]>).unwrap();
@@ -1443,13 +2199,16 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
       csv_writer.write_field(&community_cards[2].to_string()).unwrap();
       csv_writer.write_field(&community_cards[3].to_string()).unwrap();
       csv_writer.write_field("none").unwrap();
+      for f in board_texture::analyze_board(&community_cards[..4].to_vec()).ml_features() {
+        csv_writer.write_field(f).unwrap();
+      }
       csv_writer.write_field(win_ch.to_string()).unwrap();
       csv_writer.write_field(won_on_flop.to_string()).unwrap();
       csv_writer.write_field(won_on_turn.to_string()).unwrap();
       csv_writer.write_field(won_on_river.to_string()).unwrap();
       let mut flop_cards = community_cards.clone();
       flop_cards.pop();
-      let (_, real_my_hand_eq, _, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &flop_cards, &combinations, &starting_hands, &card_deck);
+      let (_, real_my_hand_eq, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &flop_cards, &combinations, &starting_hands, &card_deck);
       csv_writer.write_field(real_my_hand_eq.to_string()).unwrap();
       csv_writer.write_field(my_player_won.to_string()).unwrap();
       csv_writer.write_record(None::<&[u8]>).unwrap();
@@ -1462,11 +2221,14 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
       csv_writer.write_field(&community_cards[2].to_string()).unwrap();
       csv_writer.write_field(&community_cards[3].to_string()).unwrap();
       csv_writer.write_field(&community_cards[4].to_string()).unwrap();
+      for f in board_texture::analyze_board(&community_cards).ml_features() {
+        csv_writer.write_field(f).unwrap();
+      }
       csv_writer.write_field(win_ch.to_string()).unwrap();
       csv_writer.write_field(won_on_flop.to_string()).unwrap();
       csv_writer.write_field(won_on_turn.to_string()).unwrap();
       csv_writer.write_field(won_on_river.to_string()).unwrap();
-      let (_, real_my_hand_eq, _, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &community_cards, &combinations, &starting_hands, &card_deck);
+      let (_, real_my_hand_eq, _, _, _) = get_hand_equity_and_opponent_range(&players[my_player_idx].0, &community_cards, &combinations, &starting_hands, &card_deck);
       csv_writer.write_field(real_my_hand_eq.to_string()).unwrap();
       csv_writer.write_field(my_player_won.to_string()).unwrap();
       csv_writer.write_record(None::<&[u8]>).unwrap();
@@ -1476,10 +2238,12 @@ fn generate_ml_data(outter_runs: u32, max_sim_runs: u64, num_pl: usize, simulate
   }
 }
 
+// card_deck is what can still be dealt, so dead cards the caller took out of it are never outs or opponent cards
+#[allow(clippy::ptr_arg, clippy::needless_borrow, clippy::needless_range_loop, clippy::bool_comparison, clippy::assign_op_pattern)]
 fn get_hand_equity_and_opponent_range(
   hand: &Vec<Card>, community: &Vec<Card>, combinations: &HashMap<Vec<Card>, (f32,f32)>,
   _starting_hands: &HashMap<Vec<Card>, (f32,f32,f32)>, card_deck: &Vec<Card>
-) -> (HandRank, f32, HashMap<HandRank, i32>, HashMap<HandRank, i32>, i32, f32) {
+) -> (HandRank, f32, HashMap<HandRank, i32>, HashMap<HandRank, i32>, i32) {
     
   let (_, flop_equity, flop_hand_type, _) = get_best_hand(&hand, &community, &combinations);
 
@@ -1520,8 +2284,6 @@ fn get_hand_equity_and_opponent_range(
   let mut total_eq = 0.0;
   let mut min_eq = 1000.0;
   let mut max_eq = 0.0;
-  let mut same_hand_type_min_eq = 1000.0;
-  let mut same_hand_type_max_eq = 0.0;
   let mut opponent_hands_hash_map = HashMap::new();
   for i in 0..remaining_deck.len() {
     for j in (i+1)..remaining_deck.len() {
@@ -1539,14 +2301,6 @@ fn get_hand_equity_and_opponent_range(
       if eq > max_eq {
         max_eq = eq;
       }
-      if htype == flop_hand_type {
-        if eq < same_hand_type_min_eq {
-          same_hand_type_min_eq = eq;
-        }
-        if eq > same_hand_type_max_eq {
-          same_hand_type_max_eq = eq;
-        }
-      }
       total_eq = total_eq+eq;
       opponent_num_hands = opponent_num_hands+1;
       *opponent_hands_hash_map.entry(htype).or_insert(0) += 1;
@@ -1561,14 +2315,12 @@ fn get_hand_equity_and_opponent_range(
   }
   let real_my_hand_eq = (flop_equity-min_eq)/range_eq;
 
-  let rel_range_eq = same_hand_type_max_eq-same_hand_type_min_eq;
-  // in case if my hand is the absolute weakest in the range
-  if flop_equity < same_hand_type_min_eq {
-    same_hand_type_min_eq = flop_equity;
-  }
-  let rel_hand_eq = (flop_equity-same_hand_type_min_eq)/rel_range_eq;
+  return (flop_hand_type, real_my_hand_eq, improved_hands_hash_map, opponent_hands_hash_map, opponent_num_hands)
+}
 
-  return (flop_hand_type, real_my_hand_eq, improved_hands_hash_map, opponent_hands_hash_map, opponent_num_hands, rel_hand_eq)
+// when opponents fold we win the pot as it is, otherwise it is the same bet as in calculate_ev()
+fn calculate_ev_with_fold_equity(total_pot: f32, bet_amount: f32, win_ch: f32, fold_ch: f32) -> f32 {
+  return fold_ch*total_pot + (1.0-fold_ch)*calculate_ev(total_pot, bet_amount, win_ch)
 }
 
 fn calculate_ev(total_pot: f32, call_amount: f32, win_ch: f32) -> f32 {
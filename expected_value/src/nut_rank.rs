@@ -0,0 +1,115 @@
+use std::fmt;
+use itertools::Itertools;
+use crate::{Card, HandRank, get_best_hand_string};
+use crate::score_table::{ScoreTable, card_index};
+
+// hands that beat hero to list by default
+pub const TOP_HANDS: usize = 5;
+
+// Where hero's hand sits among every hand an opponent can hold on this board: how many combos beat it,
+// tie with it and lose to it, counted exactly instead of normalised equity.
+pub struct NutRank {
+  pub rank: HandRank,
+  pub beat: u32,
+  pub tie: u32,
+  pub lose: u32,
+  // best opponent combos that beat hero, strongest first
+  pub better: Vec<([Card; 2], HandRank)>,
+}
+
+impl NutRank {
+  // 1 is the nuts, ties share the same position
+  pub fn position(&self) -> u32 {
+    return self.beat + 1
+  }
+
+  pub fn total(&self) -> u32 {
+    return self.beat + self.tie + self.lose
+  }
+}
+
+pub struct NutAnalysis {
+  pub current: NutRank,
+  // the same for every turn or river card that can still come
+  pub next: Vec<(Card, NutRank)>,
+}
+
+fn score(hole: [&Card; 2], board: &[Card], score_table: &ScoreTable) -> f32 {
+  let mut cards = vec![card_index(hole[0]), card_index(hole[1])];
+  cards.extend(board.iter().map(card_index));
+  return score_table.best_score(&cards)
+}
+
+// card_deck is the live deck, hero's cards and the board are taken out of it here
+pub fn nut_rank(hand: &[Card], board: &[Card], card_deck: &[Card], top_n: usize, score_table: &ScoreTable) -> NutRank {
+  let hero_score = score([&hand[0], &hand[1]], board, score_table);
+  let deck: Vec<&Card> = card_deck.iter().filter(|c| !hand.contains(c) && !board.contains(c)).collect();
+  let mut beat = 0;
+  let mut tie = 0;
+  let mut lose = 0;
+  let mut better = Vec::new();
+  for i in 0..deck.len() {
+    for j in (i+1)..deck.len() {
+      let s = score([deck[i], deck[j]], board, score_table);
+      if s > hero_score {
+        beat += 1;
+        let combo = if deck[i] > deck[j] { [*deck[i], *deck[j]] } else { [*deck[j], *deck[i]] };
+        better.push((combo, s));
+      } else if s == hero_score {
+        tie += 1;
+      } else {
+        lose += 1;
+      }
+    }
+  }
+  let better = better.into_iter()
+    .sorted_by(|a, b| b.1.partial_cmp(&a.1).unwrap())
+    .take(top_n)
+    .map(|(combo, s)| (combo, get_best_hand_string(s)))
+    .collect();
+  return NutRank{rank: get_best_hand_string(hero_score), beat: beat, tie: tie, lose: lose, better: better}
+}
+
+pub fn analyze_nut_rank(hand: &[Card], board: &[Card], card_deck: &[Card], top_n: usize, score_table: &ScoreTable) -> NutAnalysis {
+  let current = nut_rank(hand, board, card_deck, top_n, score_table);
+  let mut next = Vec::new();
+  if board.len() < 5 {
+    for card in card_deck.iter().filter(|c| !hand.contains(c) && !board.contains(c)) {
+      let mut next_board = board.to_vec();
+      next_board.push(*card);
+      next.push((*card, nut_rank(hand, &next_board, card_deck, 0, score_table)));
+    }
+  }
+  return NutAnalysis{current: current, next: next}
+}
+
+impl fmt::Display for NutRank {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} of {} ({} beat, {} tie, {} lose)", self.position(), self.total(), self.beat, self.tie, self.lose)
+  }
+}
+
+impl fmt::Display for NutAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "Nut rank: {} {}", self.current.rank, self.current)?;
+    if !self.current.better.is_empty() {
+      writeln!(f, "Hands that beat hero:")?;
+      for (combo, rank) in &self.current.better {
+        writeln!(f, "  {} {}  {}", combo[0], combo[1], rank)?;
+      }
+    }
+    if !self.next.is_empty() {
+      // cards that leave hero in the same spot are on one line, best spots first
+      writeln!(f, "Next card:")?;
+      let groups = self.next.iter()
+        .sorted_by_key(|(card, r)| (r.beat, r.tie, r.rank, *card))
+        .group_by(|(_, r)| (r.beat, r.tie, r.rank));
+      for ((_, _, rank), group) in &groups {
+        let group: Vec<&(Card, NutRank)> = group.collect();
+        let cards: Vec<String> = group.iter().map(|(card, _)| card.to_string()).collect();
+        writeln!(f, "  {:<20} {}: {}", rank.to_string(), group[0].1, cards.join(" "))?;
+      }
+    }
+    Ok(())
+  }
+}
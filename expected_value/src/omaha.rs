@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::SystemTime;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use itertools::Itertools;
+use crate::{Card, HandRank, get_best_hand_string};
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index, index_to_card};
+use crate::lowball::{LowHand, EIGHT_OR_BETTER, omaha_best_low};
+use crate::variant::{self, Variant};
+
+// random deals for equity, and opponent hands sampled for every out
+const EQUITY_SIMULATIONS: usize = 20000;
+const OUT_SAMPLES: usize = 500;
+
+// Omaha hand has to use exactly two hole cards and exactly three board cards, so unlike best_score()
+// the board can never play on its own and four to a flush on board is not a flush.
+pub fn omaha_score(hole: &[u8], board: &[u8], score_table: &ScoreTable) -> f32 {
+  let mut best = 0.0;
+  for h in hole.iter().combinations(2) {
+    for b in board.iter().combinations(3) {
+      let mut cards = [*h[0], *h[1], *b[0], *b[1], *b[2]];
+      cards.sort_unstable();
+      let score = score_table.score5(&cards);
+      if score > best {
+        best = score;
+      }
+    }
+  }
+  return best
+}
+
+// Pot limit: the most you can raise to is calling first and then betting the pot that makes.
+// total_pot already has opponent's bet in it. returns chips hero puts in.
+pub fn pot_limit_max_bet(total_pot: f32, call_amount: f32) -> f32 {
+  return call_amount + (total_pot + call_amount)
+}
+
+pub struct OmahaOut {
+  pub card: Card,
+  pub rank: HandRank,
+  // share of random opponent hands that still beat hero after this card
+  pub beaten_share: f32,
+}
+
+pub struct OmahaAnalysis {
+  pub num_hole_cards: usize,
+  // Omaha Hi-Lo, pot is split with the best 8 or better low
+  pub hi_lo: bool,
+  pub rank: HandRank,
+  pub low: Option<LowHand>,
+  // share of the pot hero wins at showdown against random hands, ties and hi/lo halves split
+  pub equity: f32,
+  // hand class of a random opponent hand on the current board
+  pub opponent_ranks: HashMap<HandRank, u32>,
+  pub num_opponent_hands: u32,
+  pub outs: Vec<OmahaOut>,
+}
+
+fn remaining_deck(hole: &[u8], board: &[u8], dead: &[u8]) -> Vec<u8> {
+  return (0..NUM_CARDS as u8).filter(|c| !hole.contains(c) && !board.contains(c) && !dead.contains(c)).collect()
+}
+
+// random cards from the deck without repeats, shuffling only as much as needed
+fn deal(deck: &mut [u8], n: usize, rng: &mut ChaCha20Rng) -> Vec<u8> {
+  for i in 0..n {
+    let j = rng.gen_range(i..deck.len());
+    deck.swap(i, j);
+  }
+  return deck[..n].to_vec()
+}
+
+pub struct Omaha<'a> {
+  // 4 for PLO4, 5 for PLO5
+  pub num_hole_cards: usize,
+  pub hi_lo: bool,
+  pub score_table: &'a ScoreTable,
+}
+
+impl Variant for Omaha<'_> {
+  fn name(&self) -> String {
+    return format!("PLO{}{}", self.num_hole_cards, if self.hi_lo { " Hi-Lo" } else { "" })
+  }
+
+  fn deck(&self) -> Vec<Card> {
+    return (0..NUM_CARDS as u8).map(index_to_card).collect()
+  }
+
+  fn num_hole_cards(&self) -> usize {
+    return self.num_hole_cards
+  }
+
+  fn board_streets(&self) -> Vec<usize> {
+    return vec![3, 1, 1]
+  }
+
+  fn score(&self, hole: &[Card], board: &[Card]) -> f32 {
+    let hole: Vec<u8> = hole.iter().map(card_index).collect();
+    let board: Vec<u8> = board.iter().map(card_index).collect();
+    return omaha_score(&hole, &board, self.score_table)
+  }
+
+  fn low(&self, hole: &[Card], board: &[Card]) -> Option<LowHand> {
+    if !self.hi_lo {
+      return None
+    }
+    let hole: Vec<u8> = hole.iter().map(card_index).collect();
+    let board: Vec<u8> = board.iter().map(card_index).collect();
+    return omaha_best_low(&hole, &board, Some(EIGHT_OR_BETTER))
+  }
+
+  fn hi_lo(&self) -> bool {
+    return self.hi_lo
+  }
+}
+
+// hole: 4 cards for PLO4 or 5 for PLO5, board: flop, turn or river
+pub fn analyze_omaha(hole: &[Card], board: &[Card], dead: &[Card], num_opponents: usize, hi_lo: bool, score_table: &ScoreTable) -> OmahaAnalysis {
+  let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+  let hole_idx: Vec<u8> = hole.iter().map(card_index).collect();
+  let board_idx: Vec<u8> = board.iter().map(card_index).collect();
+  let dead_idx: Vec<u8> = dead.iter().map(card_index).collect();
+  let n = hole.len();
+  let hero_score = omaha_score(&hole_idx, &board_idx, score_table);
+  let rank = get_best_hand_string(hero_score);
+  let variant = Omaha{num_hole_cards: n, hi_lo: hi_lo, score_table: score_table};
+  let equity = variant::equity(&variant, hole, board, dead, num_opponents, &mut rng);
+  let low = if hi_lo { omaha_best_low(&hole_idx, &board_idx, Some(EIGHT_OR_BETTER)) } else { None };
+
+  let mut deck = remaining_deck(&hole_idx, &board_idx, &dead_idx);
+  let mut opponent_ranks = HashMap::new();
+  for _ in 0..EQUITY_SIMULATIONS {
+    let opp = deal(&mut deck, n, &mut rng);
+    *opponent_ranks.entry(get_best_hand_string(omaha_score(&opp, &board_idx, score_table))).or_insert(0) += 1;
+  }
+
+  // next card that moves hero into a better hand class
+  let mut outs = Vec::new();
+  if board.len() < 5 {
+    for card in remaining_deck(&hole_idx, &board_idx, &dead_idx) {
+      let mut next_board = board_idx.clone();
+      next_board.push(card);
+      let score = omaha_score(&hole_idx, &next_board, score_table);
+      let next_rank = get_best_hand_string(score);
+      if next_rank <= rank {
+        continue;
+      }
+      let mut deck = remaining_deck(&hole_idx, &next_board, &dead_idx);
+      let mut beaten = 0;
+      for _ in 0..OUT_SAMPLES {
+        let opp = deal(&mut deck, n, &mut rng);
+        if omaha_score(&opp, &next_board, score_table) > score {
+          beaten += 1;
+        }
+      }
+      outs.push(OmahaOut{card: index_to_card(card), rank: next_rank, beaten_share: beaten as f32/OUT_SAMPLES as f32});
+    }
+  }
+  return OmahaAnalysis{num_hole_cards: n, hi_lo: hi_lo, rank: rank, low: low, equity: equity, opponent_ranks: opponent_ranks, num_opponent_hands: EQUITY_SIMULATIONS as u32, outs: outs}
+}
+
+impl fmt::Display for OmahaAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.hi_lo {
+      writeln!(f, "PLO{} Hi-Lo hand: {}", self.num_hole_cards, self.rank)?;
+      match self.low {
+        Some(low) => writeln!(f, "Low: {}", low)?,
+        None => writeln!(f, "Low: none")?,
+      }
+    } else {
+      writeln!(f, "PLO{} hand: {}", self.num_hole_cards, self.rank)?;
+    }
+    writeln!(f, "Hand Equity: {:.2}%", self.equity*100.0)?;
+    writeln!(f, "Opponent hand range:")?;
+    let mut ranks: Vec<&HandRank> = self.opponent_ranks.keys().collect();
+    ranks.sort();
+    for r in ranks {
+      writeln!(f, "{:<20}:{:.1}%", r.to_string(), self.opponent_ranks[r] as f32/self.num_opponent_hands as f32*100.0)?;
+    }
+    if !self.outs.is_empty() {
+      // one line per hand class hero improves to, with how often a random hand still beats it
+      let mut by_rank: Vec<(HandRank, Vec<&OmahaOut>)> = Vec::new();
+      for (rank, group) in &self.outs.iter().sorted_by_key(|o| o.rank).group_by(|o| o.rank) {
+        by_rank.push((rank, group.collect()));
+      }
+      writeln!(f, "Outs ({}):", self.outs.len())?;
+      for (rank, outs) in by_rank {
+        let cards: Vec<String> = outs.iter().map(|o| o.card.to_string()).collect();
+        let beaten: f32 = outs.iter().map(|o| o.beaten_share).sum::<f32>()/outs.len() as f32;
+        writeln!(f, "{:<20}:{} ({} outs, beaten by {:.1}% of hands)", rank.to_string(), cards.join(" "), outs.len(), beaten*100.0)?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use crate::player_stats::PlayerStats;
+use crate::range_narrowing::Tendencies;
+
+// player types to assume when there are no stats on a player
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum OpponentProfile {
+  Nit,
+  Tag,
+  Lag,
+  CallingStation,
+  Maniac,
+  // any two cards, what the engine assumed before profiles
+  Unknown,
+}
+
+impl fmt::Display for OpponentProfile {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      OpponentProfile::Nit => write!(f, "nit"),
+      OpponentProfile::Tag => write!(f, "TAG"),
+      OpponentProfile::Lag => write!(f, "LAG"),
+      OpponentProfile::CallingStation => write!(f, "calling station"),
+      OpponentProfile::Maniac => write!(f, "maniac"),
+      OpponentProfile::Unknown => write!(f, "unknown"),
+    }
+  }
+}
+
+impl FromStr for OpponentProfile {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    return match s.to_lowercase().as_str() {
+      "nit" => Ok(OpponentProfile::Nit),
+      "tag" => Ok(OpponentProfile::Tag),
+      "lag" => Ok(OpponentProfile::Lag),
+      "station" | "calling_station" => Ok(OpponentProfile::CallingStation),
+      "maniac" => Ok(OpponentProfile::Maniac),
+      "unknown" | "any" => Ok(OpponentProfile::Unknown),
+      _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown opponent profile: {}", s))),
+    }
+  }
+}
+
+impl OpponentProfile {
+  // preflop range and aggression of the profile
+  pub fn tendencies(&self) -> Tendencies {
+    return match self {
+      OpponentProfile::Nit => Tendencies{vpip: 0.12, pfr: 0.09, three_bet: 0.03, af: 1.5, wtsd: 0.22},
+      OpponentProfile::Tag => Tendencies{vpip: 0.20, pfr: 0.16, three_bet: 0.07, af: 2.5, wtsd: 0.26},
+      OpponentProfile::Lag => Tendencies{vpip: 0.32, pfr: 0.26, three_bet: 0.10, af: 3.5, wtsd: 0.28},
+      OpponentProfile::CallingStation => Tendencies{vpip: 0.45, pfr: 0.06, three_bet: 0.02, af: 0.7, wtsd: 0.38},
+      OpponentProfile::Maniac => Tendencies{vpip: 0.60, pfr: 0.45, three_bet: 0.20, af: 5.0, wtsd: 0.33},
+      OpponentProfile::Unknown => Tendencies{vpip: 1.0, pfr: 0.20, three_bet: 0.06, af: 2.0, wtsd: 0.28},
+    }
+  }
+
+  // profile closest to a player's stats, for how often they fold to a bet
+  pub fn from_stats(stats: &PlayerStats) -> OpponentProfile {
+    let (vpip, pfr) = (stats.vpip(), stats.pfr());
+    if vpip < 0.15 {
+      return OpponentProfile::Nit
+    }
+    if vpip < 0.25 {
+      return OpponentProfile::Tag
+    }
+    if pfr < vpip*0.4 {
+      return OpponentProfile::CallingStation
+    }
+    if vpip >= 0.45 && stats.af() >= 4.0 {
+      return OpponentProfile::Maniac
+    }
+    return OpponentProfile::Lag
+  }
+
+  // how often the profile continues (calls or raises) against a half pot bet after the flop
+  fn continue_vs_half_pot(&self) -> f32 {
+    return match self {
+      OpponentProfile::Nit => 0.35,
+      OpponentProfile::Tag => 0.45,
+      OpponentProfile::Lag => 0.55,
+      OpponentProfile::CallingStation => 0.80,
+      OpponentProfile::Maniac => 0.65,
+      OpponentProfile::Unknown => 0.50,
+    }
+  }
+
+  // chance the profile folds to a bet of bet_ratio times the pot, bigger bets get more folds
+  pub fn fold_probability(&self, bet_ratio: f32) -> f32 {
+    let continue_ch = self.continue_vs_half_pot() * (1.0 - 0.2*(bet_ratio.min(2.0)-0.5));
+    return 1.0 - continue_ch.clamp(0.0, 1.0)
+  }
+}
+
+// one token per seat, in the same order as input_pos. first seat is hero, "-" or "empty" is a seat that is not in the hand.
+// returns seat index (0 is hero) with the profile of every opponent in the hand.
+// example: "hero - tag nit - station"
+pub fn parse_seat_profiles(s: &str, num_seats: usize) -> Result<Vec<(usize, OpponentProfile)>, Error> {
+  let tokens: Vec<&str> = s.split_whitespace().collect();
+  if tokens.len() != num_seats {
+    return Err(Error::new(ErrorKind::InvalidInput, format!("expecting a profile for each of {} seats, got {}: '{}'", num_seats, tokens.len(), s.trim())))
+  }
+  let mut profiles = Vec::new();
+  for (i, token) in tokens.iter().enumerate() {
+    if i == 0 || *token == "-" || token.to_lowercase() == "empty" {
+      continue;
+    }
+    profiles.push((i, token.parse::<OpponentProfile>()?));
+  }
+  return Ok(profiles)
+}
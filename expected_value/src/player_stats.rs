@@ -0,0 +1,308 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use crate::hand_history::{HandHistory, ActionKind, Site, Street};
+use crate::range_narrowing::MIN_STATS_HANDS;
+
+// HUD counters for one player. percentages are computed from these so stats can keep growing with every import
+#[derive(Clone, Default)]
+pub struct PlayerStats {
+  pub hands: u32,
+  // voluntarily put money in the pot preflop
+  pub vpip_hands: u32,
+  pub pfr_hands: u32,
+  // hands where player acted facing exactly one raise preflop, and reraised in them
+  pub three_bet_chances: u32,
+  pub three_bets: u32,
+  pub postflop_aggressive: u32,
+  pub postflop_calls: u32,
+  pub saw_flop: u32,
+  pub went_to_showdown: u32,
+}
+
+fn percent(n: u32, total: u32) -> f32 {
+  if total == 0 {
+    return 0.0
+  }
+  return n as f32/total as f32
+}
+
+impl PlayerStats {
+  pub fn vpip(&self) -> f32 {
+    return percent(self.vpip_hands, self.hands)
+  }
+
+  pub fn pfr(&self) -> f32 {
+    return percent(self.pfr_hands, self.hands)
+  }
+
+  pub fn three_bet(&self) -> f32 {
+    return percent(self.three_bets, self.three_bet_chances)
+  }
+
+  // aggression factor: (bets + raises) / calls after the flop
+  pub fn af(&self) -> f32 {
+    if self.postflop_calls == 0 {
+      return self.postflop_aggressive as f32
+    }
+    return self.postflop_aggressive as f32/self.postflop_calls as f32
+  }
+
+  // went to showdown, out of hands where player saw the flop
+  pub fn wtsd(&self) -> f32 {
+    return percent(self.went_to_showdown, self.saw_flop)
+  }
+}
+
+impl fmt::Display for PlayerStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "VPIP: {:.1}%, PFR: {:.1}%, 3Bet: {:.1}%, AF: {:.2}, WTSD: {:.1}% ({} hands)",
+      self.vpip()*100.0, self.pfr()*100.0, self.three_bet()*100.0, self.af(), self.wtsd()*100.0, self.hands)
+  }
+}
+
+// column order of the counters in the players table, same as the fields of PlayerStats
+const COUNTERS: &str = "hands, vpip_hands, pfr_hands, three_bet_chances, three_bets, postflop_aggressive, postflop_calls, saw_flop, went_to_showdown";
+
+fn db_error(e: rusqlite::Error) -> Error {
+  return Error::other(format!("player stats db: {}", e))
+}
+
+fn stats_from_row(row: &Row, first: usize) -> rusqlite::Result<PlayerStats> {
+  return Ok(PlayerStats{
+    hands: row.get(first)?,
+    vpip_hands: row.get(first+1)?,
+    pfr_hands: row.get(first+2)?,
+    three_bet_chances: row.get(first+3)?,
+    three_bets: row.get(first+4)?,
+    postflop_aggressive: row.get(first+5)?,
+    postflop_calls: row.get(first+6)?,
+    saw_flop: row.get(first+7)?,
+    went_to_showdown: row.get(first+8)?,
+  })
+}
+
+// counters every player of the hand adds to their stats
+fn hand_stats(hh: &HandHistory) -> Vec<(String, PlayerStats)> {
+  let mut vpip = HashSet::new();
+  let mut pfr = HashSet::new();
+  let mut three_bet_chances = HashSet::new();
+  let mut three_bets = HashSet::new();
+  let mut folded = HashSet::new();
+  let mut num_raises = 0;
+  for a in hh.street_actions(Street::Preflop) {
+    let voluntary = matches!(a.kind, ActionKind::Fold | ActionKind::Check | ActionKind::Call | ActionKind::Bet | ActionKind::Raise);
+    if voluntary && num_raises == 1 && !pfr.contains(&a.player) {
+      three_bet_chances.insert(a.player.clone());
+      if a.kind == ActionKind::Raise {
+        three_bets.insert(a.player.clone());
+      }
+    }
+    match a.kind {
+      ActionKind::Call => {
+        vpip.insert(a.player.clone());
+      },
+      ActionKind::Bet | ActionKind::Raise => {
+        vpip.insert(a.player.clone());
+        pfr.insert(a.player.clone());
+        num_raises += 1;
+      },
+      ActionKind::Fold => {
+        folded.insert(a.player.clone());
+      },
+      _ => {},
+    }
+  }
+  let saw_flop = hh.board.len() >= 3;
+
+  let mut postflop = HashMap::<String, (u32, u32)>::new();
+  for a in &hh.actions {
+    if a.street == Street::Preflop {
+      continue;
+    }
+    match a.kind {
+      ActionKind::Bet | ActionKind::Raise => postflop.entry(a.player.clone()).or_insert((0, 0)).0 += 1,
+      ActionKind::Call => postflop.entry(a.player.clone()).or_insert((0, 0)).1 += 1,
+      ActionKind::Fold => {
+        folded.insert(a.player.clone());
+      },
+      _ => {},
+    }
+  }
+  let num_left = hh.seats.iter().filter(|s| hh.actions.iter().any(|a| a.player == s.player) && !folded.contains(&s.player)).count();
+
+  let mut res = Vec::new();
+  for seat in &hh.seats {
+    let player = &seat.player;
+    // players sitting out have no actions and no cards
+    if !hh.actions.iter().any(|a| &a.player == player) {
+      continue;
+    }
+    let mut stats = PlayerStats{hands: 1, ..Default::default()};
+    stats.vpip_hands = vpip.contains(player) as u32;
+    stats.pfr_hands = pfr.contains(player) as u32;
+    stats.three_bet_chances = three_bet_chances.contains(player) as u32;
+    stats.three_bets = three_bets.contains(player) as u32;
+    if let Some((aggressive, calls)) = postflop.get(player) {
+      stats.postflop_aggressive = *aggressive;
+      stats.postflop_calls = *calls;
+    }
+    let folded_preflop = hh.street_actions(Street::Preflop).iter().any(|a| &a.player == player && a.kind == ActionKind::Fold);
+    if saw_flop && !folded_preflop {
+      stats.saw_flop = 1;
+      if !folded.contains(player) && (num_left > 1 || hh.showdown.contains(player)) {
+        stats.went_to_showdown = 1;
+      }
+    }
+    res.push((player.clone(), stats));
+  }
+  return res
+}
+
+// SQLite file with a row of counters per player. importing adds to the rows of players in new hands
+// instead of rewriting the whole file, and every lookup reads the current counters.
+pub struct PlayerStatsDb {
+  conn: Connection,
+}
+
+impl PlayerStatsDb {
+  // creates the file and tables when they don't exist yet
+  pub fn open(path: &str) -> Result<PlayerStatsDb, Error> {
+    let conn = Connection::open(path).map_err(db_error)?;
+    conn.execute_batch("
+      CREATE TABLE IF NOT EXISTS players (
+        name TEXT PRIMARY KEY,
+        hands INTEGER NOT NULL, vpip_hands INTEGER NOT NULL, pfr_hands INTEGER NOT NULL,
+        three_bet_chances INTEGER NOT NULL, three_bets INTEGER NOT NULL,
+        postflop_aggressive INTEGER NOT NULL, postflop_calls INTEGER NOT NULL,
+        saw_flop INTEGER NOT NULL, went_to_showdown INTEGER NOT NULL
+      );
+      -- so importing the same file twice doesn't count hands twice
+      CREATE TABLE IF NOT EXISTS hands (id TEXT PRIMARY KEY);
+    ").map_err(db_error)?;
+    return Ok(PlayerStatsDb{conn: conn})
+  }
+
+  pub fn get(&self, player: &str) -> Result<Option<PlayerStats>, Error> {
+    let sql = format!("SELECT {} FROM players WHERE name = ?1", COUNTERS);
+    return self.conn.query_row(&sql, params![player], |row| stats_from_row(row, 0)).optional().map_err(db_error)
+  }
+
+  // every player, most hands first
+  pub fn all(&self) -> Result<Vec<(String, PlayerStats)>, Error> {
+    let sql = format!("SELECT name, {} FROM players ORDER BY hands DESC, name", COUNTERS);
+    let mut stmt = self.conn.prepare(&sql).map_err(db_error)?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, stats_from_row(row, 1)?))).map_err(db_error)?;
+    return rows.collect::<rusqlite::Result<Vec<(String, PlayerStats)>>>().map_err(db_error)
+  }
+
+  pub fn num_players(&self) -> Result<usize, Error> {
+    return self.conn.query_row("SELECT COUNT(*) FROM players", [], |row| row.get(0)).map_err(db_error)
+  }
+
+  // adds every player's actions from the hands in one transaction. hands that are already in the db,
+  // or come from an anonymous table where names are only seat labels (Ignition), are skipped.
+  // returns number of hands added
+  pub fn add_hands(&mut self, hands: &[HandHistory]) -> Result<usize, Error> {
+    let tx = self.conn.transaction().map_err(db_error)?;
+    let upsert = format!("INSERT INTO players (name, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+      ON CONFLICT(name) DO UPDATE SET hands = hands + excluded.hands, vpip_hands = vpip_hands + excluded.vpip_hands,
+        pfr_hands = pfr_hands + excluded.pfr_hands, three_bet_chances = three_bet_chances + excluded.three_bet_chances,
+        three_bets = three_bets + excluded.three_bets, postflop_aggressive = postflop_aggressive + excluded.postflop_aggressive,
+        postflop_calls = postflop_calls + excluded.postflop_calls, saw_flop = saw_flop + excluded.saw_flop,
+        went_to_showdown = went_to_showdown + excluded.went_to_showdown", COUNTERS);
+    let mut added = 0;
+    for hh in hands {
+      if hh.site == Site::Ignition {
+        continue;
+      }
+      let key = format!("{}#{}", hh.site, hh.hand_id);
+      if tx.execute("INSERT OR IGNORE INTO hands (id) VALUES (?1)", params![key]).map_err(db_error)? == 0 {
+        continue;
+      }
+      for (player, s) in hand_stats(hh) {
+        tx.execute(&upsert, params![player, s.hands, s.vpip_hands, s.pfr_hands, s.three_bet_chances, s.three_bets,
+          s.postflop_aggressive, s.postflop_calls, s.saw_flop, s.went_to_showdown]).map_err(db_error)?;
+      }
+      added += 1;
+    }
+    tx.commit().map_err(db_error)?;
+    return Ok(added)
+  }
+}
+
+// one name per seat in the same order as input_pos, the same way as opponent profiles: first seat is hero,
+// "-" is a seat that is not in the hand. returns seat index (0 is hero), name and stats of every named opponent
+// with enough hands in the db to be used instead of a profile.
+// example: "hero - villain42 - - fish99"
+pub fn seat_stats(s: &str, num_seats: usize, db: &PlayerStatsDb) -> Result<Vec<(usize, String, PlayerStats)>, Error> {
+  let tokens: Vec<&str> = s.split_whitespace().collect();
+  if tokens.len() != num_seats {
+    return Err(Error::new(ErrorKind::InvalidInput, format!("expecting a player name for each of {} seats, got {}: '{}'", num_seats, tokens.len(), s.trim())))
+  }
+  let mut res = Vec::new();
+  for (i, name) in tokens.iter().enumerate() {
+    if i == 0 || *name == "-" {
+      continue;
+    }
+    match db.get(name)? {
+      Some(stats) if stats.hands >= MIN_STATS_HANDS => res.push((i, name.to_string(), stats)),
+      Some(stats) => println!("Not enough hands on {} to use stats: {}", name, stats.hands),
+      None => println!("No stats for player: {}", name),
+    }
+  }
+  return Ok(res)
+}
+
+pub fn print_player_stats(db: &PlayerStatsDb, player: Option<&str>) -> Result<(), Error> {
+  match player {
+    Some(p) => match db.get(p)? {
+      Some(stats) => println!("{}: {}", p, stats),
+      None => println!("No stats for player: {}", p),
+    },
+    None => {
+      for (name, stats) in db.all()? {
+        println!("{:<20}:{}", name, stats);
+      }
+    },
+  }
+  return Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::hand_history::parse_hand_history;
+  use crate::hand_history::tests::POKERSTARS_HAND;
+
+  #[test]
+  fn import_adds_up_counters() {
+    let mut db = PlayerStatsDb::open(":memory:").unwrap();
+    let hh = parse_hand_history(POKERSTARS_HAND).unwrap();
+    assert_eq!(db.add_hands(std::slice::from_ref(&hh)).unwrap(), 1);
+    // the same hand again is not counted
+    assert_eq!(db.add_hands(&[hh]).unwrap(), 0);
+    // sitting out player has no row
+    assert_eq!(db.num_players().unwrap(), 3);
+    let p3 = db.get("player3").unwrap().unwrap();
+    assert_eq!((p3.hands, p3.vpip_hands, p3.pfr_hands, p3.saw_flop, p3.postflop_aggressive), (1, 1, 1, 1, 1));
+    let hero = db.get("Hero").unwrap().unwrap();
+    assert_eq!((hero.vpip_hands, hero.pfr_hands, hero.three_bet_chances, hero.three_bets), (1, 0, 1, 0));
+    assert!(db.get("player5").unwrap().is_none());
+  }
+
+  #[test]
+  fn seat_stats_need_enough_hands() {
+    let db = PlayerStatsDb::open(":memory:").unwrap();
+    let regular = PlayerStats{hands: MIN_STATS_HANDS, ..Default::default()};
+    let newcomer = PlayerStats{hands: 1, ..Default::default()};
+    for (name, s) in [("regular", regular), ("newcomer", newcomer)].iter() {
+      db.conn.execute(&format!("INSERT INTO players (name, {}) VALUES (?1, ?2, 0, 0, 0, 0, 0, 0, 0, 0)", COUNTERS), params![name, s.hands]).unwrap();
+    }
+    let seats = seat_stats("hero regular - newcomer unknown", 5, &db).unwrap();
+    assert_eq!(seats.len(), 1);
+    assert_eq!((seats[0].0, seats[0].1.as_str(), seats[0].2.hands), (1, "regular", MIN_STATS_HANDS));
+    assert!(seat_stats("hero regular", 5, &db).is_err());
+  }
+}
@@ -0,0 +1,533 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use serde::{Serialize, Deserialize};
+use crate::Card;
+use crate::score_table::ScoreTable;
+
+// 13 pairs + 78 suited + 78 offsuit
+pub const NUM_CLASSES: usize = 169;
+
+// Starting hand classes are laid out as the usual 13x13 grid, aces first:
+// pairs are on the diagonal, suited hands above it and offsuit hands below it.
+// so class = row*13+col, where row/col are 14-rank.
+pub fn class_from_ranks(high: u8, low: u8, suited: bool) -> usize {
+  let r1 = (14-high) as usize;
+  let r2 = (14-low) as usize;
+  if high == low || suited {
+    return r1*13+r2
+  }
+  return r2*13+r1
+}
+
+// returns (high rank, low rank, suited)
+pub fn class_ranks(class: usize) -> (u8, u8, bool) {
+  let row = class / 13;
+  let col = class % 13;
+  if row <= col {
+    return (14-row as u8, 14-col as u8, row != col)
+  }
+  return (14-col as u8, 14-row as u8, false)
+}
+
+pub fn starting_hand_class(hand: &[Card]) -> usize {
+  let (low, high) = if hand[0].rank <= hand[1].rank { (hand[0], hand[1]) } else { (hand[1], hand[0]) };
+  return class_from_ranks(high.rank, low.rank, high.suit == low.suit)
+}
+
+fn rank_char(rank: u8) -> char {
+  return match rank {
+    2..=9 => (b'0'+rank) as char,
+    10 => 'T',
+    11 => 'J',
+    12 => 'Q',
+    13 => 'K',
+    14 => 'A',
+    _ => panic!("unknown rank: {}", rank),
+  }
+}
+
+fn char_rank(c: char) -> Option<u8> {
+  return match c.to_ascii_uppercase() {
+    '2'..='9' => Some(c as u8 - b'0'),
+    'T' => Some(10),
+    'J' => Some(11),
+    'Q' => Some(12),
+    'K' => Some(13),
+    'A' => Some(14),
+    _ => None,
+  }
+}
+
+pub fn class_name(class: usize) -> String {
+  let (high, low, suited) = class_ranks(class);
+  if high == low {
+    return format!("{}{}", rank_char(high), rank_char(low))
+  }
+  return format!("{}{}{}", rank_char(high), rank_char(low), if suited { 's' } else { 'o' })
+}
+
+// all concrete combos of a class as sorted card indices
+pub fn class_combos(class: usize) -> Vec<[u8; 2]> {
+  let (high, low, suited) = class_ranks(class);
+  let mut res = Vec::new();
+  for s1 in 0..4u8 {
+    for s2 in 0..4u8 {
+      if high == low && s2 <= s1 {
+        continue;
+      }
+      if high != low && suited != (s1 == s2) {
+        continue;
+      }
+      let c1 = (low-2)*4 + s1;
+      let c2 = (high-2)*4 + s2;
+      res.push(if c1 < c2 { [c1, c2] } else { [c2, c1] });
+    }
+  }
+  return res
+}
+
+// Weighted set of starting hand classes, e.g. "QQ+, AKs, ATs+, KQo, 22-55"
+// Tokens:
+//   AA, AKs, AKo   - single class
+//   AK             - both suited and offsuit
+//   QQ+            - QQ and all higher pairs
+//   ATs+, AT+      - kicker goes up to one rank below high card
+//   22-55, A2s-A5s - inclusive span
+//   any            - all 169 classes
+// any token can have a weight suffix, e.g. "AKo:0.5"
+#[derive(Clone)]
+pub struct PreflopRange {
+  pub weights: Vec<f32>,
+}
+
+impl PreflopRange {
+  pub fn empty() -> PreflopRange {
+    return PreflopRange{weights: vec![0.0; NUM_CLASSES]}
+  }
+
+  pub fn any() -> PreflopRange {
+    return PreflopRange{weights: vec![1.0; NUM_CLASSES]}
+  }
+
+  pub fn from_class(class: usize) -> PreflopRange {
+    let mut range = PreflopRange::empty();
+    range.weights[class] = 1.0;
+    return range
+  }
+
+  pub fn contains(&self, class: usize) -> bool {
+    return self.weights[class] > 0.0
+  }
+
+  // number of concrete combos in the range, taking weights into account
+  pub fn num_combos(&self) -> f32 {
+    let mut total = 0.0;
+    for class in 0..NUM_CLASSES {
+      total += self.weights[class] * class_combos(class).len() as f32;
+    }
+    return total
+  }
+}
+
+fn invalid_range(token: &str) -> Error {
+  return Error::new(ErrorKind::InvalidInput, format!("unknown range token: '{}'", token))
+}
+
+// parses "AKs" style token into (high, low, suitedness). suitedness is None when both are allowed.
+fn parse_class_token(token: &str) -> Result<(u8, u8, Option<bool>), Error> {
+  let chars: Vec<char> = token.chars().collect();
+  if chars.len() < 2 || chars.len() > 3 {
+    return Err(invalid_range(token))
+  }
+  let r1 = char_rank(chars[0]).ok_or_else(|| invalid_range(token))?;
+  let r2 = char_rank(chars[1]).ok_or_else(|| invalid_range(token))?;
+  let (high, low) = if r1 >= r2 { (r1, r2) } else { (r2, r1) };
+  let suited = match chars.get(2) {
+    None => None,
+    Some('s') | Some('S') => Some(true),
+    Some('o') | Some('O') => Some(false),
+    _ => return Err(invalid_range(token)),
+  };
+  if high == low && suited.is_some() {
+    return Err(invalid_range(token))
+  }
+  return Ok((high, low, suited))
+}
+
+fn add_classes(range: &mut PreflopRange, high: u8, low: u8, suited: Option<bool>, weight: f32) {
+  if high == low {
+    range.weights[class_from_ranks(high, low, false)] = weight;
+    return
+  }
+  if suited != Some(false) {
+    range.weights[class_from_ranks(high, low, true)] = weight;
+  }
+  if suited != Some(true) {
+    range.weights[class_from_ranks(high, low, false)] = weight;
+  }
+}
+
+impl FromStr for PreflopRange {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut range = PreflopRange::empty();
+    for raw_token in s.split([',', ' ']) {
+      let raw_token = raw_token.trim();
+      if raw_token.is_empty() {
+        continue;
+      }
+      let (token, weight) = match raw_token.find(':') {
+        Some(idx) => {
+          let w = raw_token[idx+1..].parse::<f32>().map_err(|_| invalid_range(raw_token))?;
+          (&raw_token[..idx], w)
+        },
+        None => (raw_token, 1.0),
+      };
+      if token.to_lowercase() == "any" {
+        for class in 0..NUM_CLASSES {
+          range.weights[class] = weight;
+        }
+      } else if let Some(base) = token.strip_suffix('+') {
+        let (high, low, suited) = parse_class_token(base)?;
+        if high == low {
+          for r in low..=14 {
+            add_classes(&mut range, r, r, None, weight);
+          }
+        } else {
+          for r in low..high {
+            add_classes(&mut range, high, r, suited, weight);
+          }
+        }
+      } else if token.contains('-') {
+        let parts: Vec<&str> = token.split('-').collect();
+        if parts.len() != 2 {
+          return Err(invalid_range(token))
+        }
+        let (h1, l1, s1) = parse_class_token(parts[0])?;
+        let (h2, l2, s2) = parse_class_token(parts[1])?;
+        if s1 != s2 {
+          return Err(invalid_range(token))
+        }
+        if h1 == l1 && h2 == l2 {
+          for r in cmp::min(l1, l2)..=cmp::max(l1, l2) {
+            add_classes(&mut range, r, r, None, weight);
+          }
+        } else if h1 == h2 {
+          for r in cmp::min(l1, l2)..=cmp::max(l1, l2) {
+            add_classes(&mut range, h1, r, s1, weight);
+          }
+        } else {
+          return Err(invalid_range(token))
+        }
+      } else {
+        let (high, low, suited) = parse_class_token(token)?;
+        add_classes(&mut range, high, low, suited, weight);
+      }
+    }
+    return Ok(range)
+  }
+}
+
+// All-in preflop equity of every starting hand class against every other class.
+// equity[hero*NUM_CLASSES+villain] is hero's share of the pot (ties count as half),
+// averaged over all non-overlapping combo pairs and all 5 card boards.
+// combos holds the number of those combo pairs, which is used to weight range vs range lookups.
+#[derive(Serialize, Deserialize)]
+pub struct PreflopMatrix {
+  equity: Vec<f32>,
+  combos: Vec<u16>,
+}
+
+impl PreflopMatrix {
+  pub fn equity(&self, hero: usize, villain: usize) -> f32 {
+    return self.equity[hero*NUM_CLASSES+villain]
+  }
+
+  pub fn num_combos(&self, hero: usize, villain: usize) -> u16 {
+    return self.combos[hero*NUM_CLASSES+villain]
+  }
+
+  pub fn hand_equity(&self, hero: &[Card], villain: &[Card]) -> f32 {
+    return self.equity(starting_hand_class(hero), starting_hand_class(villain))
+  }
+
+  // equity of hero's range against villain's range. each pair of classes is weighted by
+  // the number of combo pairs that can actually be dealt together.
+  pub fn range_equity(&self, hero: &PreflopRange, villain: &PreflopRange) -> f32 {
+    let mut total_eq = 0.0;
+    let mut total_weight = 0.0;
+    for h in 0..NUM_CLASSES {
+      if !hero.contains(h) {
+        continue;
+      }
+      for v in 0..NUM_CLASSES {
+        if !villain.contains(v) {
+          continue;
+        }
+        let w = hero.weights[h] * villain.weights[v] * self.num_combos(h, v) as f32;
+        total_eq += w * self.equity(h, v);
+        total_weight += w;
+      }
+    }
+    if total_weight == 0.0 {
+      return 0.0
+    }
+    return total_eq/total_weight
+  }
+
+  // equity of a single class against a range
+  pub fn class_vs_range_equity(&self, hero: usize, villain: &PreflopRange) -> f32 {
+    return self.range_equity(&PreflopRange::from_class(hero), villain)
+  }
+}
+
+// all 24 ways to relabel suits
+fn suit_permutations() -> Vec<[u8; 4]> {
+  let mut res = Vec::new();
+  for a in 0..4u8 {
+    for b in 0..4u8 {
+      for c in 0..4u8 {
+        for d in 0..4u8 {
+          if a != b && a != c && a != d && b != c && b != d && c != d {
+            res.push([a, b, c, d]);
+          }
+        }
+      }
+    }
+  }
+  return res
+}
+
+fn permute_combo(combo: &[u8; 2], perm: &[u8; 4]) -> [u8; 2] {
+  let c1 = combo[0] - combo[0]%4 + perm[(combo[0]%4) as usize];
+  let c2 = combo[1] - combo[1]%4 + perm[(combo[1]%4) as usize];
+  return if c1 < c2 { [c1, c2] } else { [c2, c1] }
+}
+
+// hero combo, villain combo and how many combo pairs share their equity
+type Matchup = ([u8; 2], [u8; 2], u32);
+
+// groups combo pairs of two classes that are the same up to relabeling of suits.
+// equity is identical within a group, so only one representative of each has to be enumerated.
+fn canonical_matchups(hero: usize, villain: usize, perms: &Vec<[u8; 4]>) -> (Vec<Matchup>, u16) {
+  let mut groups = HashMap::<([u8; 2], [u8; 2]), u32>::new();
+  let mut num_pairs = 0;
+  for h in class_combos(hero) {
+    for v in class_combos(villain) {
+      if h[0] == v[0] || h[0] == v[1] || h[1] == v[0] || h[1] == v[1] {
+        continue;
+      }
+      num_pairs += 1;
+      let mut key = (h, v);
+      for perm in perms {
+        let candidate = (permute_combo(&h, perm), permute_combo(&v, perm));
+        if candidate < key {
+          key = candidate;
+        }
+      }
+      *groups.entry(key).or_insert(0) += 1;
+    }
+  }
+  let res = groups.into_iter().map(|((h, v), n)| (h, v, n)).collect();
+  return (res, num_pairs)
+}
+
+// enumerates every board and returns (hero wins, ties, total boards)
+fn enumerate_matchup(hero: &[u8; 2], villain: &[u8; 2], score_table: &ScoreTable) -> (u64, u64, u64) {
+  let deck: Vec<u8> = (0..52u8).filter(|c| !hero.contains(c) && !villain.contains(c)).collect();
+  let n = deck.len();
+  let mut wins = 0;
+  let mut ties = 0;
+  let mut total = 0;
+  let mut hero_cards = [hero[0], hero[1], 0, 0, 0, 0, 0];
+  let mut villain_cards = [villain[0], villain[1], 0, 0, 0, 0, 0];
+  for a in 0..n {
+    for b in (a+1)..n {
+      for c in (b+1)..n {
+        for d in (c+1)..n {
+          for e in (d+1)..n {
+            let board = [deck[a], deck[b], deck[c], deck[d], deck[e]];
+            hero_cards[2..].copy_from_slice(&board);
+            villain_cards[2..].copy_from_slice(&board);
+            let hero_score = score_table.best_score(&hero_cards);
+            let villain_score = score_table.best_score(&villain_cards);
+            if hero_score > villain_score {
+              wins += 1;
+            } else if hero_score == villain_score {
+              ties += 1;
+            }
+            total += 1;
+          }
+        }
+      }
+    }
+  }
+  return (wins, ties, total)
+}
+
+// hero class equity against villain class over all their combo pairs, and the number of those pairs
+fn class_matchup_equity(hero: usize, villain: usize, perms: &Vec<[u8; 4]>, score_table: &ScoreTable) -> (f32, u16) {
+  let (matchups, num_pairs) = canonical_matchups(hero, villain, perms);
+  let mut total_eq = 0.0;
+  let mut total_weight = 0.0;
+  for (h, v, weight) in matchups {
+    let (wins, ties, total) = enumerate_matchup(&h, &v, score_table);
+    total_eq += weight as f64 * (wins as f64 + ties as f64/2.0) / total as f64;
+    total_weight += weight as f64;
+  }
+  return ((total_eq/total_weight) as f32, num_pairs)
+}
+
+pub fn generate_preflop_matrix(combinations: &HashMap<Vec<Card>,(f32,f32)>) -> PreflopMatrix {
+  let score_table = ScoreTable::new(combinations);
+  let perms = suit_permutations();
+  let mut matrix = PreflopMatrix{equity: vec![0.5; NUM_CLASSES*NUM_CLASSES], combos: vec![0; NUM_CLASSES*NUM_CLASSES]};
+
+  // class vs itself is always 0.5 by symmetry, and villain vs hero is 1-(hero vs villain).
+  // so only upper triangle needs to be enumerated.
+  let mut cells = Vec::new();
+  for h in 0..NUM_CLASSES {
+    let (_, num_pairs) = canonical_matchups(h, h, &perms);
+    matrix.combos[h*NUM_CLASSES+h] = num_pairs;
+    for v in (h+1)..NUM_CLASSES {
+      cells.push((h, v));
+    }
+  }
+
+  let num_threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  let next_cell = AtomicUsize::new(0);
+  let results = Mutex::new(Vec::<(usize, usize, f32, u16)>::new());
+  thread::scope(|s| {
+    for _ in 0..num_threads {
+      s.spawn(|| {
+        loop {
+          let idx = next_cell.fetch_add(1, Ordering::SeqCst);
+          if idx >= cells.len() {
+            break;
+          }
+          let (h, v) = cells[idx];
+          println!("Computing preflop matrix {}/{}: {} vs {}", idx+1, cells.len(), class_name(h), class_name(v));
+          let (eq, num_pairs) = class_matchup_equity(h, v, &perms, &score_table);
+          results.lock().unwrap().push((h, v, eq, num_pairs));
+        }
+      });
+    }
+  });
+
+  for (h, v, eq, num_pairs) in results.into_inner().unwrap() {
+    matrix.equity[h*NUM_CLASSES+v] = eq;
+    matrix.equity[v*NUM_CLASSES+h] = 1.0-eq;
+    matrix.combos[h*NUM_CLASSES+v] = num_pairs;
+    matrix.combos[v*NUM_CLASSES+h] = num_pairs;
+  }
+  return matrix
+}
+
+pub fn load_or_generate_preflop_matrix(path: &str, combinations: &HashMap<Vec<Card>,(f32,f32)>) -> PreflopMatrix {
+  if Path::new(path).exists() {
+    let f = BufReader::new(File::open(path).unwrap());
+    return bincode::deserialize_from(f).unwrap()
+  }
+  println!("Generating preflop matrix...");
+  let matrix = generate_preflop_matrix(combinations);
+  let mut f = BufWriter::new(File::create(path).unwrap());
+  bincode::serialize_into(&mut f, &matrix).unwrap();
+  return matrix
+}
+
+// example: "AKo" "QQ" or "QQ+,AKs" "any"
+pub fn print_preflop_equity(matrix: &PreflopMatrix, hero_str: &str, villain_str: &str) -> Result<(), Error> {
+  let hero = hero_str.parse::<PreflopRange>()?;
+  let villain = villain_str.parse::<PreflopRange>()?;
+  let eq = matrix.range_equity(&hero, &villain);
+  println!("{} ({:.0} combos) vs {} ({:.0} combos): {:.2}% / {:.2}%", hero_str, hero.num_combos(), villain_str, villain.num_combos(), eq*100.0, (1.0-eq)*100.0);
+  return Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::score_table::tests::holdem_score_table;
+
+  fn class(name: &str) -> usize {
+    return (0..NUM_CLASSES).find(|c| class_name(*c) == name).unwrap()
+  }
+
+  fn classes(range: &PreflopRange) -> Vec<String> {
+    return (0..NUM_CLASSES).filter(|c| range.contains(*c)).map(class_name).collect()
+  }
+
+  #[test]
+  fn class_layout() {
+    assert_eq!(class_name(0), "AA");
+    assert_eq!(class_name(1), "AKs");
+    assert_eq!(class_name(13), "AKo");
+    assert_eq!(class_name(NUM_CLASSES-1), "22");
+    for c in 0..NUM_CLASSES {
+      let (high, low, suited) = class_ranks(c);
+      assert_eq!(class_from_ranks(high, low, suited), c);
+    }
+    assert_eq!(class_combos(class("AA")).len(), 6);
+    assert_eq!(class_combos(class("AKs")).len(), 4);
+    assert_eq!(class_combos(class("AKo")).len(), 12);
+  }
+
+  #[test]
+  fn range_tokens() {
+    let range: PreflopRange = "QQ+, ATs+, 22-44, KQ".parse().unwrap();
+    assert_eq!(classes(&range), vec!["AA", "AKs", "AQs", "AJs", "ATs", "KK", "KQs", "KQo", "QQ", "44", "33", "22"]);
+    let range: PreflopRange = "A2s-A4s".parse().unwrap();
+    assert_eq!(classes(&range), vec!["A4s", "A3s", "A2s"]);
+    let range: PreflopRange = "K9o+".parse().unwrap();
+    assert_eq!(classes(&range), vec!["KQo", "KJo", "KTo", "K9o"]);
+    assert_eq!(range.num_combos(), 48.0);
+  }
+
+  #[test]
+  fn range_weights() {
+    let range: PreflopRange = "AKo:0.5, AA".parse().unwrap();
+    assert_eq!(range.weights[class("AKo")], 0.5);
+    assert_eq!(range.weights[class("AA")], 1.0);
+    assert_eq!(range.num_combos(), 12.0);
+    // later tokens overwrite earlier ones
+    let range: PreflopRange = "any:0.25, QQ+".parse().unwrap();
+    assert_eq!(range.weights[class("72o")], 0.25);
+    assert_eq!(range.weights[class("KK")], 1.0);
+  }
+
+  #[test]
+  fn any_is_every_combo() {
+    let range: PreflopRange = "ANY".parse().unwrap();
+    assert_eq!(classes(&range).len(), NUM_CLASSES);
+    assert_eq!(range.num_combos(), 1326.0);
+    assert_eq!(PreflopRange::any().num_combos(), 1326.0);
+  }
+
+  #[test]
+  fn bad_tokens() {
+    for bad in ["AAs", "AX", "A", "AKx", "AKs-QJs", "22-A5s", "A2s-A5o", "AK:x", "22-33-44", "AKQ"] {
+      assert!(bad.parse::<PreflopRange>().is_err(), "{} parsed", bad);
+    }
+  }
+
+  #[test]
+  fn matchup_is_symmetric() {
+    let perms = suit_permutations();
+    let score_table = holdem_score_table();
+    let (aa, kk) = (class("AA"), class("KK"));
+    let (aa_eq, aa_pairs) = class_matchup_equity(aa, kk, &perms, score_table);
+    let (kk_eq, kk_pairs) = class_matchup_equity(kk, aa, &perms, score_table);
+    assert_eq!((aa_pairs, kk_pairs), (36, 36));
+    assert!((aa_eq + kk_eq - 1.0).abs() < 1e-5, "{} + {}", aa_eq, kk_eq);
+    assert!((aa_eq - 0.82).abs() < 0.01, "AA vs KK: {}", aa_eq);
+  }
+}
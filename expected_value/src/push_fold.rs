@@ -0,0 +1,256 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+use crate::preflop_matrix::{PreflopMatrix, PreflopRange, NUM_CLASSES, class_name};
+
+// fictitious play rounds, ranges are the average of all best responses
+const ITERATIONS: usize = 200;
+// class is in a chart when it's shoved or called more often than this
+const CHART_THRESHOLD: f32 = 0.5;
+// stack depths for the chart of how deep every hand can be shoved
+pub const MAX_CHART_BB: u32 = 20;
+// below this effective stack push/fold hint is shown with the analysis
+pub const HINT_THRESHOLD_BB: f32 = 15.0;
+
+// Everyone has the same stack behind after antes, blinds are 0.5 and 1 bb.
+// Model: first player in either shoves or folds, players behind them call or fold. Once someone
+// calls, the rest fold, so all-ins are always heads up.
+#[derive(Clone, Copy)]
+pub struct PushFoldConfig {
+  pub num_players: usize,
+  pub stack_bb: f32,
+  pub ante_bb: f32,
+}
+
+impl PushFoldConfig {
+  fn blind(&self, position: usize) -> f32 {
+    if position == self.num_players-1 {
+      return 1.0
+    }
+    if position == self.num_players-2 {
+      return 0.5
+    }
+    return 0.0
+  }
+
+  fn dead_money(&self) -> f32 {
+    return self.num_players as f32*self.ante_bb + 1.5
+  }
+
+  // what is in the pot when `shover` gets called by `caller`
+  fn all_in_pot(&self, shover: usize, caller: usize) -> f32 {
+    return self.dead_money() - self.blind(shover) - self.blind(caller) + 2.0*self.stack_bb
+  }
+}
+
+// position name by order of preflop action, 0 is first to act and the big blind is last
+pub fn position_name(position: usize, num_players: usize) -> String {
+  let from_end = num_players - 1 - position;
+  return match from_end {
+    0 => "BB".to_string(),
+    1 => "SB".to_string(),
+    2 => "BTN".to_string(),
+    3 => "CO".to_string(),
+    4 => "HJ".to_string(),
+    _ if position == 0 => "UTG".to_string(),
+    _ => format!("UTG+{}", position),
+  }
+}
+
+pub struct PushFoldSolution {
+  pub config: PushFoldConfig,
+  // shove[position], the big blind never shoves first in
+  pub shove: Vec<PreflopRange>,
+  // call[caller][shover]
+  pub call: Vec<Vec<PreflopRange>>,
+}
+
+// chance of the caller having a hand from their range and its equity against it, given hero's class
+fn call_chance_and_equity(matrix: &PreflopMatrix, hero: usize, range: &PreflopRange) -> (f32, f32) {
+  let mut in_range = 0.0;
+  let mut total = 0.0;
+  let mut eq = 0.0;
+  for v in 0..NUM_CLASSES {
+    let n = matrix.num_combos(hero, v) as f32;
+    total += n;
+    let w = range.weights[v]*n;
+    in_range += w;
+    eq += w*matrix.equity(hero, v);
+  }
+  if in_range == 0.0 {
+    return (0.0, 0.5)
+  }
+  return (in_range/total, eq/in_range)
+}
+
+// chip EV of shoving a class first in. antes are already in, folding loses the blind
+fn shove_ev(matrix: &PreflopMatrix, config: &PushFoldConfig, call: &[Vec<PreflopRange>], shover: usize, hero: usize) -> f32 {
+  let stack = config.stack_bb;
+  let mut ev = 0.0;
+  let mut nobody_called = 1.0;
+  for (caller, calls) in call.iter().enumerate().take(config.num_players).skip(shover+1) {
+    let (p_call, eq) = call_chance_and_equity(matrix, hero, &calls[shover]);
+    ev += nobody_called*p_call*(eq*config.all_in_pot(shover, caller) - stack);
+    nobody_called *= 1.0-p_call;
+  }
+  // everyone folded, shover gets their blind back with the dead money
+  ev += nobody_called*(config.dead_money() - config.blind(shover));
+  return ev
+}
+
+pub fn solve_push_fold(matrix: &PreflopMatrix, config: PushFoldConfig) -> PushFoldSolution {
+  let n = config.num_players;
+  let mut shove = vec![PreflopRange::any(); n];
+  shove[n-1] = PreflopRange::empty();
+  let mut call = vec![vec![PreflopRange::any(); n]; n];
+  for it in 1..=ITERATIONS {
+    let step = 1.0/(it as f32 + 1.0);
+    for (caller, calls) in call.iter_mut().enumerate().skip(1) {
+      for (shover, range) in shove.iter().enumerate().take(caller) {
+        let pot = config.all_in_pot(shover, caller);
+        let fold_ev = -config.blind(caller);
+        for v in 0..NUM_CLASSES {
+          let eq = matrix.class_vs_range_equity(v, range);
+          let best = if eq*pot - config.stack_bb > fold_ev { 1.0 } else { 0.0 };
+          calls[shover].weights[v] += (best - calls[shover].weights[v])*step;
+        }
+      }
+    }
+    for (shover, range) in shove.iter_mut().enumerate().take(n-1) {
+      for h in 0..NUM_CLASSES {
+        let best = if shove_ev(matrix, &config, &call, shover, h) > -config.blind(shover) { 1.0 } else { 0.0 };
+        range.weights[h] += (best - range.weights[h])*step;
+      }
+    }
+  }
+  return PushFoldSolution{config: config, shove: shove, call: call}
+}
+
+// 13x13 grid, aces first, suited hands above the diagonal
+fn print_grid(cell: &dyn Fn(usize) -> String) {
+  for row in 0..13 {
+    let line: Vec<String> = (0..13).map(|col| format!("{:>4}", cell(row*13+col))).collect();
+    println!("{}", line.join(""));
+  }
+}
+
+fn print_range_grid(range: &PreflopRange) {
+  print_grid(&|class| if range.weights[class] >= CHART_THRESHOLD { class_name(class) } else { ".".to_string() });
+}
+
+// share of all 1326 combos in the chart
+fn range_share(range: &PreflopRange) -> f32 {
+  let mut chart = PreflopRange::empty();
+  for class in 0..NUM_CLASSES {
+    if range.weights[class] >= CHART_THRESHOLD {
+      chart.weights[class] = 1.0;
+    }
+  }
+  return chart.num_combos()/1326.0
+}
+
+impl fmt::Display for PushFoldConfig {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} players, {:.1}bb", self.num_players, self.stack_bb)?;
+    if self.ante_bb > 0.0 {
+      write!(f, ", ante {:.2}bb", self.ante_bb)?;
+    }
+    return Ok(())
+  }
+}
+
+pub fn print_push_fold_solution(solution: &PushFoldSolution) {
+  let n = solution.config.num_players;
+  println!("Push/fold equilibrium: {}", solution.config);
+  for shover in 0..n-1 {
+    println!("{} shove ({:.1}%):", position_name(shover, n), range_share(&solution.shove[shover])*100.0);
+    print_range_grid(&solution.shove[shover]);
+  }
+  for caller in 1..n {
+    for shover in 0..caller {
+      println!("{} call vs {} ({:.1}%):", position_name(caller, n), position_name(shover, n), range_share(&solution.call[caller][shover])*100.0);
+      print_range_grid(&solution.call[caller][shover]);
+    }
+  }
+}
+
+// deepest stack in whole bb at which every class is still shoved first in, for every position
+pub fn push_fold_chart(matrix: &PreflopMatrix, num_players: usize, ante_bb: f32) -> Vec<Vec<u32>> {
+  let mut chart = vec![vec![0; NUM_CLASSES]; num_players-1];
+  for stack in 1..=MAX_CHART_BB {
+    let solution = solve_push_fold(matrix, PushFoldConfig{num_players: num_players, stack_bb: stack as f32, ante_bb: ante_bb});
+    for (row, range) in chart.iter_mut().zip(solution.shove.iter()) {
+      for (max_stack, weight) in row.iter_mut().zip(range.weights.iter()) {
+        if *weight >= CHART_THRESHOLD {
+          *max_stack = stack;
+        }
+      }
+    }
+  }
+  return chart
+}
+
+pub fn print_push_fold_chart(chart: &[Vec<u32>], num_players: usize, ante_bb: f32) {
+  println!("Deepest stack (bb) to shove first in, {} players, ante {:.2}bb ({}+ means at every depth up to {}bb):", num_players, ante_bb, MAX_CHART_BB, MAX_CHART_BB);
+  for (shover, depths) in chart.iter().enumerate() {
+    println!("{}:", position_name(shover, num_players));
+    print_grid(&|class| match depths[class] {
+      0 => ".".to_string(),
+      MAX_CHART_BB => format!("{}+", MAX_CHART_BB),
+      d => d.to_string(),
+    });
+  }
+}
+
+// one line advice for hero's hand: shove or fold first in, and whether to call shoves from players before hero
+pub fn push_fold_hint(matrix: &PreflopMatrix, config: PushFoldConfig, position: usize, class: usize, facing_shove: bool) -> String {
+  let solution = solve_push_fold(matrix, config);
+  let n = config.num_players;
+  let mut res = format!("Push/fold ({}, {}): ", config, position_name(position, n));
+  if !facing_shove {
+    if position == n-1 {
+      return res + "BB can't be first in"
+    }
+    let shove = solution.shove[position].weights[class] >= CHART_THRESHOLD;
+    return res + &format!("{} {}", if shove { "SHOVE" } else { "FOLD" }, class_name(class))
+  }
+  let calls: Vec<String> = (0..position).map(|shover| {
+    let call = solution.call[position][shover].weights[class] >= CHART_THRESHOLD;
+    format!("vs {} {}", position_name(shover, n), if call { "CALL" } else { "FOLD" })
+  }).collect();
+  res += &format!("{} {}", class_name(class), calls.join(", "));
+  return res
+}
+
+// hero's stack at the start of the hand, big blind and ante in the same units as the pot, e.g. "$12.40 $1 $0.10"
+#[derive(Clone, Copy)]
+pub struct StackInfo {
+  pub stack: f32,
+  pub big_blind: f32,
+  pub ante: f32,
+}
+
+impl FromStr for StackInfo {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let amounts = s.split_whitespace().map(|v| {
+      let v = v.replace(['$', ','], "");
+      lexical::parse::<f32, _>(&v).map_err(|e| Error::new(ErrorKind::InvalidInput, format!("bad amount '{}': {:?}", v, e)))
+    }).collect::<Result<Vec<f32>, Error>>()?;
+    if amounts.len() != 2 && amounts.len() != 3 {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("expecting stack, big blind and optional ante, got: '{}'", s)))
+    }
+    if amounts[1] <= 0.0 {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("big blind must be positive, got: '{}'", s)))
+    }
+    return Ok(StackInfo{stack: amounts[0], big_blind: amounts[1], ante: amounts.get(2).cloned().unwrap_or(0.0)})
+  }
+}
+
+impl StackInfo {
+  // push/fold model wants the stack behind after the ante is posted
+  pub fn push_fold_config(&self, num_players: usize) -> PushFoldConfig {
+    return PushFoldConfig{num_players: num_players, stack_bb: (self.stack - self.ante)/self.big_blind, ante_bb: self.ante/self.big_blind}
+  }
+}
@@ -0,0 +1,316 @@
+use std::fmt;
+use std::time::SystemTime;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha20Rng;
+use itertools::Itertools;
+use crate::{Card, HandRank, get_best_hand_string};
+use crate::preflop_matrix::{PreflopRange, NUM_CLASSES, class_combos};
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index, index_to_card};
+
+// flop ranges of a few hundred combos each take a few seconds when every runout is enumerated,
+// wider ones compare combos and score hands on a random sample of runouts instead
+const MAX_PAIR_EVALS: usize = 300_000_000;
+const MAX_SCORE_EVALS: usize = 300_000;
+const MIN_SAMPLED_RUNOUTS: usize = 100;
+pub const NUM_BUCKETS: usize = 10;
+
+// hands above this equity are strong, below WEAK_EQUITY they are air, the rest are medium
+const STRONG_EQUITY: f32 = 0.75;
+const WEAK_EQUITY: f32 = 0.25;
+const BAR_WIDTH: usize = 40;
+
+// Equity of every combo in a range with its weight, from the best down. The histogram shows how much of the range
+// is in each equity bucket, the curve shows equity against how far into the range the combo is, like study tools plot it.
+pub struct EquityDistribution {
+  pub points: Vec<(f32, f32)>,
+}
+
+impl EquityDistribution {
+  pub fn new(mut points: Vec<(f32, f32)>) -> EquityDistribution {
+    points.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    return EquityDistribution{points: points}
+  }
+
+  fn total_weight(&self) -> f32 {
+    return self.points.iter().map(|p| p.1).sum()
+  }
+
+  // share of the range (by weight) with equity in each 10% bucket, from 0-10% up
+  pub fn histogram(&self) -> [f32; NUM_BUCKETS] {
+    let total = self.total_weight();
+    let mut buckets = [0.0; NUM_BUCKETS];
+    for (equity, weight) in &self.points {
+      let b = ((equity*NUM_BUCKETS as f32) as usize).min(NUM_BUCKETS-1);
+      buckets[b] += weight/total;
+    }
+    return buckets
+  }
+
+  // equity of the combo at every 10% of the range, best combos first
+  pub fn curve(&self) -> Vec<(f32, f32)> {
+    let total = self.total_weight();
+    let mut res = Vec::new();
+    let mut seen = 0.0;
+    let mut next = 0;
+    for (equity, weight) in &self.points {
+      seen += weight/total;
+      while next < NUM_BUCKETS && seen >= (next as f32 + 0.5)/NUM_BUCKETS as f32 {
+        res.push(((next as f32 + 0.5)/NUM_BUCKETS as f32, *equity));
+        next += 1;
+      }
+    }
+    return res
+  }
+
+  // shares of strong, medium and weak (air) hands
+  pub fn strength_shares(&self) -> (f32, f32, f32) {
+    let total = self.total_weight();
+    let share = |f: &dyn Fn(f32) -> bool| self.points.iter().filter(|p| f(p.0)).fold(0.0, |sum, p| sum + p.1)/total;
+    return (share(&|e| e > STRONG_EQUITY), share(&|e| (WEAK_EQUITY..=STRONG_EQUITY).contains(&e)), share(&|e| e < WEAK_EQUITY))
+  }
+}
+
+impl fmt::Display for EquityDistribution {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.points.is_empty() {
+      return Ok(())
+    }
+    let (strong, medium, weak) = self.strength_shares();
+    writeln!(f, "Strong (>{:.0}%): {:.1}%, Medium: {:.1}%, Air (<{:.0}%): {:.1}%", STRONG_EQUITY*100.0, strong*100.0, medium*100.0, WEAK_EQUITY*100.0, weak*100.0)?;
+    writeln!(f, "Equity histogram:")?;
+    let histogram = self.histogram();
+    let max = histogram.iter().cloned().fold(0.0, f32::max);
+    for (b, share) in histogram.iter().enumerate() {
+      let bar = "#".repeat((share/max*BAR_WIDTH as f32).round() as usize);
+      writeln!(f, "{:>3}-{:<3}%: {:5.1}% {}", b*100/NUM_BUCKETS, (b+1)*100/NUM_BUCKETS, share*100.0, bar)?;
+    }
+    writeln!(f, "Equity curve (share of range, best first):")?;
+    for (at, equity) in self.curve() {
+      let bar = "#".repeat((equity*BAR_WIDTH as f32).round() as usize);
+      writeln!(f, "{:>5.0}%: {:5.1}% {}", at*100.0, equity*100.0, bar)?;
+    }
+    Ok(())
+  }
+}
+
+fn mask(cards: &[u8]) -> u64 {
+  return cards.iter().fold(0, |m, c| m | (1 << c))
+}
+
+pub struct ComboEquity {
+  pub cards: [u8; 2],
+  pub weight: f32,
+  pub equity: f32,
+  // hand class on the current board
+  pub rank: HandRank,
+}
+
+impl ComboEquity {
+  pub fn name(&self) -> String {
+    return format!("{}{}", index_to_card(self.cards[1]), index_to_card(self.cards[0]))
+  }
+}
+
+pub struct RangeEquity {
+  // sorted from the best equity down
+  pub combos: Vec<ComboEquity>,
+  pub equity: f32,
+}
+
+impl RangeEquity {
+  pub fn total_weight(&self) -> f32 {
+    return self.combos.iter().map(|c| c.weight).sum()
+  }
+
+  // share of the range (by weight) in each hand class, from worst to best
+  pub fn rank_shares(&self) -> Vec<(HandRank, f32)> {
+    let total = self.total_weight();
+    let mut shares = Vec::new();
+    for (rank, group) in &self.combos.iter().sorted_by_key(|c| c.rank).group_by(|c| c.rank) {
+      shares.push((rank, group.map(|c| c.weight).sum::<f32>()/total));
+    }
+    return shares
+  }
+
+  pub fn distribution(&self) -> EquityDistribution {
+    return EquityDistribution::new(self.combos.iter().map(|c| (c.equity, c.weight)).collect())
+  }
+}
+
+pub struct RangeVsRange {
+  pub board: Vec<Card>,
+  pub hero: RangeEquity,
+  pub villain: RangeEquity,
+  pub num_runouts: usize,
+  pub total_runouts: usize,
+}
+
+// concrete combos of a range that don't use board or dead cards
+fn range_combos(range: &PreflopRange, blocked: u64) -> Vec<([u8; 2], f32)> {
+  let mut res = Vec::new();
+  for class in 0..NUM_CLASSES {
+    if !range.contains(class) {
+      continue;
+    }
+    for combo in class_combos(class) {
+      if mask(&combo) & blocked == 0 {
+        res.push((combo, range.weights[class]));
+      }
+    }
+  }
+  return res
+}
+
+// every turn and river that can still come, each as the cards to add to the board
+fn board_runouts(board: &[u8], blocked: u64) -> Vec<Vec<u8>> {
+  let deck: Vec<u8> = (0..NUM_CARDS as u8).filter(|c| blocked & (1 << c) == 0).collect();
+  return deck.into_iter().combinations(5 - board.len()).collect()
+}
+
+// Equity of every combo in hero's range against villain's range on a 3 to 5 card board. Both ranges are
+// weighted preflop ranges, combo pairs that share a card are skipped and every runout counts the same.
+pub fn range_vs_range(hero: &PreflopRange, villain: &PreflopRange, board: &[Card], dead: &[Card], score_table: &ScoreTable) -> RangeVsRange {
+  let blocked = mask(&board.iter().chain(dead.iter()).map(card_index).collect::<Vec<u8>>());
+  return combos_vs_combos(range_combos(hero, blocked), range_combos(villain, blocked), board, dead, score_table)
+}
+
+// hero's hand against every combo of villain's range, hero's cards block villain's combos
+pub fn hand_vs_range(hand: &[Card], villain: &PreflopRange, board: &[Card], dead: &[Card], score_table: &ScoreTable) -> RangeVsRange {
+  return hand_vs_combos(hand, range_combos(villain, 0), board, dead, score_table)
+}
+
+// hero's hand against villain's combos with a weight each, like a range narrowed by villain's actions
+pub fn hand_vs_combos(hand: &[Card], villain_combos: Vec<([u8; 2], f32)>, board: &[Card], dead: &[Card], score_table: &ScoreTable) -> RangeVsRange {
+  let mut combo = [card_index(&hand[0]), card_index(&hand[1])];
+  combo.sort_unstable();
+  let blocked = mask(&board.iter().chain(dead.iter()).map(card_index).collect::<Vec<u8>>()) | mask(&combo);
+  let villain_combos = villain_combos.into_iter().filter(|(c, w)| *w > 0.0 && mask(c) & blocked == 0).collect();
+  return combos_vs_combos(vec![(combo, 1.0)], villain_combos, board, dead, score_table)
+}
+
+fn combos_vs_combos(hero_combos: Vec<([u8; 2], f32)>, villain_combos: Vec<([u8; 2], f32)>, board: &[Card], dead: &[Card], score_table: &ScoreTable) -> RangeVsRange {
+  let board_idx: Vec<u8> = board.iter().map(card_index).collect();
+  let dead_idx: Vec<u8> = dead.iter().map(card_index).collect();
+  let blocked = mask(&board_idx) | mask(&dead_idx);
+  let hero_masks: Vec<u64> = hero_combos.iter().map(|(c, _)| mask(c)).collect();
+  let villain_masks: Vec<u64> = villain_combos.iter().map(|(c, _)| mask(c)).collect();
+
+  let mut runouts = board_runouts(&board_idx, blocked);
+  let total_runouts = runouts.len();
+  let pairs = (hero_combos.len()*villain_combos.len()).max(1);
+  let hands = (hero_combos.len() + villain_combos.len()).max(1);
+  let max_runouts = (MAX_PAIR_EVALS/pairs).min(MAX_SCORE_EVALS/hands).max(MIN_SAMPLED_RUNOUTS);
+  // a single hand against any range is at most 1081 flop runouts of 1082 hands, so it is always exact
+  if hero_combos.len() > 1 && runouts.len() > max_runouts {
+    let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+    runouts.shuffle(&mut rng);
+    runouts.truncate(max_runouts);
+  }
+
+  // won and total weight of opponent combos faced, for every combo on both sides
+  let mut hero_won = vec![0.0; hero_combos.len()];
+  let mut hero_faced = vec![0.0; hero_combos.len()];
+  let mut villain_won = vec![0.0; villain_combos.len()];
+  let mut villain_faced = vec![0.0; villain_combos.len()];
+  let mut hero_scores = vec![0.0; hero_combos.len()];
+  let mut villain_scores = vec![0.0; villain_combos.len()];
+  let mut cards = [0u8; 7];
+  for runout in &runouts {
+    let runout_mask = mask(runout);
+    let n = board_idx.len();
+    cards[2..2+n].copy_from_slice(&board_idx);
+    cards[2+n..].copy_from_slice(runout);
+    for (i, (combo, _)) in hero_combos.iter().enumerate() {
+      if hero_masks[i] & runout_mask != 0 {
+        continue;
+      }
+      cards[0] = combo[0];
+      cards[1] = combo[1];
+      hero_scores[i] = score_table.best_score(&cards);
+    }
+    for (j, (combo, _)) in villain_combos.iter().enumerate() {
+      if villain_masks[j] & runout_mask != 0 {
+        continue;
+      }
+      cards[0] = combo[0];
+      cards[1] = combo[1];
+      villain_scores[j] = score_table.best_score(&cards);
+    }
+    for i in 0..hero_combos.len() {
+      if hero_masks[i] & runout_mask != 0 {
+        continue;
+      }
+      for j in 0..villain_combos.len() {
+        if villain_masks[j] & (runout_mask | hero_masks[i]) != 0 {
+          continue;
+        }
+        let share = if hero_scores[i] > villain_scores[j] { 1.0 } else if hero_scores[i] == villain_scores[j] { 0.5 } else { 0.0 };
+        hero_won[i] += villain_combos[j].1*share;
+        hero_faced[i] += villain_combos[j].1;
+        villain_won[j] += hero_combos[i].1*(1.0-share);
+        villain_faced[j] += hero_combos[i].1;
+      }
+    }
+  }
+
+  let range_equity = |combos: &Vec<([u8; 2], f32)>, won: &Vec<f32>, faced: &Vec<f32>| -> RangeEquity {
+    let mut res = Vec::new();
+    let mut total_won = 0.0;
+    let mut total_faced = 0.0;
+    for (i, (combo, weight)) in combos.iter().enumerate() {
+      if faced[i] == 0.0 {
+        continue;
+      }
+      let mut current = combo.to_vec();
+      current.extend(board_idx.iter());
+      let rank = get_best_hand_string(score_table.best_score(&current));
+      res.push(ComboEquity{cards: *combo, weight: *weight, equity: won[i]/faced[i], rank: rank});
+      total_won += weight*won[i];
+      total_faced += weight*faced[i];
+    }
+    res.sort_by(|a, b| b.equity.partial_cmp(&a.equity).unwrap());
+    let equity = if total_faced > 0.0 { total_won/total_faced } else { 0.0 };
+    return RangeEquity{combos: res, equity: equity}
+  };
+  let hero = range_equity(&hero_combos, &hero_won, &hero_faced);
+  let villain = range_equity(&villain_combos, &villain_won, &villain_faced);
+  return RangeVsRange{board: board.to_vec(), hero: hero, villain: villain, num_runouts: runouts.len(), total_runouts: total_runouts}
+}
+
+impl RangeVsRange {
+  // hero's equity against each of villain's combos, which is what is left of villain's equity against hero
+  pub fn hero_vs_combos(&self) -> EquityDistribution {
+    return EquityDistribution::new(self.villain.combos.iter().map(|c| (1.0-c.equity, c.weight)).collect())
+  }
+}
+
+fn write_range(f: &mut fmt::Formatter, name: &str, range: &RangeEquity) -> fmt::Result {
+  writeln!(f, "{} ({:.0} combos): {:.2}%", name, range.total_weight(), range.equity*100.0)?;
+  writeln!(f, "Hand range:")?;
+  for (rank, share) in range.rank_shares() {
+    writeln!(f, "{:<20}:{:.1}%", rank.to_string(), share*100.0)?;
+  }
+  write!(f, "{}", range.distribution())?;
+  writeln!(f, "Combos:")?;
+  for c in &range.combos {
+    let weight = if c.weight != 1.0 { format!(" (x{:.2})", c.weight) } else { String::new() };
+    writeln!(f, "{}  {:6.2}%  {}{}", c.name(), c.equity*100.0, c.rank, weight)?;
+  }
+  Ok(())
+}
+
+impl fmt::Display for RangeVsRange {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let board: Vec<String> = self.board.iter().map(|c| c.to_string()).collect();
+    writeln!(f, "Board: {}", board.join(" "))?;
+    if self.num_runouts < self.total_runouts {
+      writeln!(f, "Runouts: {} sampled out of {}", self.num_runouts, self.total_runouts)?;
+    }
+    writeln!(f, "Equity: hero {:.2}% / villain {:.2}%", self.hero.equity*100.0, self.villain.equity*100.0)?;
+    writeln!(f)?;
+    write_range(f, "Hero", &self.hero)?;
+    writeln!(f)?;
+    write_range(f, "Villain", &self.villain)
+  }
+}
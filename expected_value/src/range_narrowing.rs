@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha20Rng;
+use crate::{Card, TablePosition};
+use crate::hand_history::{HandHistory, ActionKind, Street};
+use crate::player_stats::PlayerStats;
+use crate::range_equity::hand_vs_combos;
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index};
+
+// below this many hands player's stats are too noisy, position defaults are used instead
+pub const MIN_STATS_HANDS: u32 = 30;
+// how sharp the edge of a range is, in strength percentiles
+const PREFLOP_SHARPNESS: f32 = 0.03;
+const POSTFLOP_SHARPNESS: f32 = 0.08;
+// deals of a multiway pot to sample, about 0.4% standard error on equity
+const MULTIWAY_DEALS: usize = 20_000;
+// average VPIP at 6 max, used to scale player's overall VPIP to a position
+const AVERAGE_VPIP: f32 = 0.25;
+
+// how often a player puts money in and how aggressive they are, from stats or defaults by position
+#[derive(Clone, Copy)]
+pub struct Tendencies {
+  pub vpip: f32,
+  pub pfr: f32,
+  pub three_bet: f32,
+  pub af: f32,
+  pub wtsd: f32,
+}
+
+fn position_vpip(position: Option<TablePosition>) -> f32 {
+  return match position {
+    Some(TablePosition::Early) => 0.15,
+    Some(TablePosition::Mid) => 0.19,
+    Some(TablePosition::Late) => 0.26,
+    Some(TablePosition::Button) => 0.40,
+    Some(TablePosition::SB) => 0.32,
+    // big blind defends wide, it already has a blind in
+    None => 0.38,
+  }
+}
+
+pub fn tendencies(position: Option<TablePosition>, stats: Option<&PlayerStats>) -> Tendencies {
+  let vpip = position_vpip(position);
+  let default = Tendencies{vpip: vpip, pfr: vpip*0.75, three_bet: 0.06, af: 2.0, wtsd: 0.28};
+  return match stats {
+    Some(s) if s.hands >= MIN_STATS_HANDS => {
+      let overall = Tendencies{vpip: s.vpip(), pfr: s.pfr(), three_bet: s.three_bet(), af: s.af(), wtsd: s.wtsd()};
+      at_position(&overall, position)
+    },
+    _ => default,
+  }
+}
+
+// player's overall VPIP and PFR scaled to a position, tighter early and wider on the button. any two cards stay any two cards
+pub fn at_position(t: &Tendencies, position: Option<TablePosition>) -> Tendencies {
+  if t.vpip >= 1.0 {
+    return *t
+  }
+  let scale = position_vpip(position)/AVERAGE_VPIP;
+  return Tendencies{vpip: (t.vpip*scale).min(1.0), pfr: (t.pfr*scale).min(1.0), ..*t}
+}
+
+fn card_mask(cards: &[u8]) -> u64 {
+  return cards.iter().fold(0, |m, c| m | (1 << c))
+}
+
+fn sigmoid(x: f32) -> f32 {
+  return 1.0/(1.0+(-x).exp())
+}
+
+// close to 1 for hands in the top share of all hands, close to 0 outside of it
+fn top_share(strength: f32, share: f32, sharpness: f32) -> f32 {
+  return sigmoid((strength-(1.0-share))/sharpness)
+}
+
+// chance of a preflop action given hand strength percentile (1.0 is AA)
+fn preflop_likelihood(kind: ActionKind, strength: f32, num_raises: u32, t: &Tendencies) -> f32 {
+  return match kind {
+    ActionKind::Raise | ActionKind::Bet => {
+      let share = if num_raises == 0 { t.pfr } else { t.three_bet };
+      top_share(strength, share, PREFLOP_SHARPNESS)
+    },
+    // vpip share is already in the prior, hands at the top of the range would rather raise
+    ActionKind::Call => 1.0 - 0.7*top_share(strength, t.pfr, PREFLOP_SHARPNESS),
+    ActionKind::Check => 1.0 - 0.8*top_share(strength, t.pfr, PREFLOP_SHARPNESS),
+    _ => 1.0,
+  }
+}
+
+// chance of a postflop action given made hand strength percentile on the board.
+// bigger bets and raises come from stronger hands, aggressive players bluff more, players that go to showdown a lot call wider
+fn postflop_likelihood(kind: ActionKind, strength: f32, has_draw: bool, bet_ratio: f32, t: &Tendencies) -> f32 {
+  let bet_ratio = bet_ratio.min(2.0);
+  return match kind {
+    ActionKind::Bet | ActionKind::Raise => {
+      let mut threshold = 0.55 + 0.1*bet_ratio - 0.03*t.af.min(4.0);
+      if kind == ActionKind::Raise {
+        threshold += 0.1;
+      }
+      let value = sigmoid((strength-threshold)/POSTFLOP_SHARPNESS);
+      let bluff = if has_draw { 0.5 } else { (0.02 + 0.03*t.af).clamp(0.02, 0.25) };
+      value + (1.0-value)*bluff
+    },
+    ActionKind::Call => {
+      let threshold = 0.35 + 0.1*bet_ratio - (t.wtsd-0.28);
+      let value = sigmoid((strength-threshold)/POSTFLOP_SHARPNESS);
+      let floats = if has_draw { 0.8 } else { 0.02 };
+      value + (1.0-value)*floats
+    },
+    // strong hands are mostly bet, some are slowplayed
+    ActionKind::Check => 1.0 - 0.7*sigmoid((strength-0.8)/POSTFLOP_SHARPNESS),
+    _ => 1.0,
+  }
+}
+
+// flush draw or 4 ranks of a straight with a hole card in them, cheap enough to run for every combo
+fn has_draw(combo: &Vec<Card>, board: &[Card]) -> bool {
+  if board.len() >= 5 {
+    return false
+  }
+  for hole in combo {
+    let suited = board.iter().filter(|c| c.suit == hole.suit).count() + combo.iter().filter(|c| c.suit == hole.suit).count();
+    if suited == 4 {
+      return true
+    }
+  }
+  let mut present = [false; 15];
+  for c in combo.iter().chain(board.iter()) {
+    present[c.rank as usize] = true;
+    if c.rank == 14 {
+      present[1] = true;
+    }
+  }
+  for low in 1..=10usize {
+    let n = (low..low+5).filter(|r| present[*r]).count();
+    let uses_hole = combo.iter().any(|c| (low..low+5).contains(&(c.rank as usize)) || (c.rank == 14 && low == 1));
+    if n == 4 && uses_hole {
+      return true
+    }
+  }
+  return false
+}
+
+// every 2 card combo with preflop strength percentile, built once and reused for all narrowing
+pub struct RangeModel {
+  pub combos: Vec<Vec<Card>>,
+  preflop_strength: Vec<f32>,
+}
+
+impl RangeModel {
+  pub fn new(card_deck: &[Card], starting_hands: &HashMap<Vec<Card>, (f32,f32,f32)>) -> RangeModel {
+    let mut combos = Vec::new();
+    for i in 0..card_deck.len() {
+      for j in (i+1)..card_deck.len() {
+        let mut h = vec![card_deck[i], card_deck[j]];
+        h.sort();
+        combos.push(h);
+      }
+    }
+    let avg_eqs: Vec<f32> = combos.iter().map(|h| starting_hands[h].1).collect();
+    let mut sorted = avg_eqs.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let preflop_strength = avg_eqs.iter().map(|eq| percentile(&sorted, *eq)).collect();
+    return RangeModel{combos: combos, preflop_strength: preflop_strength}
+  }
+
+  // uniform weights, minus combos that use a card we can see
+  pub fn full_range(&self, dead_cards: &[Card]) -> Vec<f32> {
+    return self.combos.iter().map(|h| if h.iter().any(|c| dead_cards.contains(c)) { 0.0 } else { 1.0 }).collect()
+  }
+
+  // hands player puts money in with preflop, the top vpip share of all hands
+  pub fn preflop_range(&self, t: &Tendencies, dead_cards: &[Card]) -> Vec<f32> {
+    let mut weights = self.full_range(dead_cards);
+    if t.vpip >= 1.0 {
+      return weights
+    }
+    for (w, strength) in weights.iter_mut().zip(self.preflop_strength.iter()) {
+      *w *= top_share(*strength, t.vpip, PREFLOP_SHARPNESS);
+    }
+    return weights
+  }
+
+  // made hand strength percentile of every live combo on the board
+  fn postflop_strength(&self, board: &[Card], weights: &[f32], score_table: &ScoreTable) -> Vec<f32> {
+    let mut cards: Vec<u8> = vec![0, 0];
+    cards.extend(board.iter().map(card_index));
+    let scores: Vec<f32> = self.combos.iter().map(|h| {
+      cards[0] = card_index(&h[0]);
+      cards[1] = card_index(&h[1]);
+      score_table.best_score(&cards)
+    }).collect();
+    let mut sorted: Vec<f32> = scores.iter().zip(weights.iter()).filter(|(_, w)| **w > 0.0).map(|(s, _)| *s).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    return scores.iter().map(|s| percentile(&sorted, *s)).collect()
+  }
+
+  // Bayesian update of player's combo weights with every action they took before action index `upto`.
+  // prior is their preflop range from position and stats when they put money in voluntarily (any two cards for a big blind
+  // that checked), each action multiplies weights by its likelihood for the combo
+  pub fn narrow(&self, hh: &HandHistory, player: &str, upto: usize, dead_cards: &[Card], t: &Tendencies, score_table: &ScoreTable) -> Vec<f32> {
+    let entered_pot = hh.actions.iter().take(upto)
+      .any(|a| a.player == player && a.street == Street::Preflop && matches!(a.kind, ActionKind::Call | ActionKind::Bet | ActionKind::Raise));
+    let mut weights = if entered_pot { self.preflop_range(t, dead_cards) } else { self.full_range(dead_cards) };
+    let mut street = Street::Preflop;
+    let mut strength: Vec<f32> = self.preflop_strength.clone();
+    let mut total_pot = 0.0;
+    let mut num_raises = 0;
+    for action in hh.actions.iter().take(upto) {
+      if action.street != street {
+        street = action.street;
+        let board = hh.board_on(street);
+        for (i, h) in self.combos.iter().enumerate() {
+          if h.iter().any(|c| board.contains(c)) {
+            weights[i] = 0.0;
+          }
+        }
+        strength = self.postflop_strength(&board, &weights, score_table);
+      }
+      if action.player == player {
+        let board = hh.board_on(street);
+        let bet_ratio = if total_pot > 0.0 { action.amount/total_pot } else { 1.0 };
+        for i in 0..self.combos.len() {
+          if weights[i] == 0.0 {
+            continue;
+          }
+          weights[i] *= if street == Street::Preflop {
+            preflop_likelihood(action.kind, strength[i], num_raises, t)
+          } else {
+            postflop_likelihood(action.kind, strength[i], has_draw(&self.combos[i], &board), bet_ratio, t)
+          };
+        }
+      }
+      if street == Street::Preflop && action.kind == ActionKind::Raise {
+        num_raises += 1;
+      }
+      if action.kind == ActionKind::Return {
+        total_pot -= action.amount;
+      } else {
+        total_pot += action.amount;
+      }
+    }
+    return weights
+  }
+
+  // Range of an opponent in a live hand, where only the board and the bet hero faces are known: preflop range from
+  // their tendencies without the seen cards, and when there is a bet to call, narrowed as the player that made it
+  pub fn live_range(&self, t: &Tendencies, board: &[Card], seen_cards: &[Card], bet_ratio: Option<f32>, score_table: &ScoreTable) -> Vec<f32> {
+    let mut weights = self.preflop_range(t, seen_cards);
+    if let Some(bet_ratio) = bet_ratio {
+      let strength = self.postflop_strength(board, &weights, score_table);
+      for i in 0..self.combos.len() {
+        if weights[i] > 0.0 {
+          weights[i] *= postflop_likelihood(ActionKind::Bet, strength[i], has_draw(&self.combos[i], board), bet_ratio, t);
+        }
+      }
+    }
+    return weights
+  }
+
+  // hero's equity against the weighted opponent combos, every turn and river that can still come is dealt out
+  pub fn equity_vs_range(&self, hand: &[Card], board: &[Card], dead: &[Card], weights: &[f32], score_table: &ScoreTable) -> f32 {
+    let villain_combos = self.combos.iter().zip(weights.iter())
+      .map(|(h, w)| ([card_index(&h[0]), card_index(&h[1])], *w))
+      .collect();
+    return hand_vs_combos(hand, villain_combos, board, dead, score_table).hero.equity
+  }
+
+  // hero's equity against every opponent's weighted range at once. each deal gives all opponents a combo from their
+  // range without sharing cards with each other, runs out the board and gives the pot to the best hand, split on ties
+  pub fn equity_vs_ranges(&self, hand: &[Card], board: &[Card], dead: &[Card], ranges: &[Vec<f32>], score_table: &ScoreTable) -> f32 {
+    if ranges.len() == 1 {
+      return self.equity_vs_range(hand, board, dead, &ranges[0], score_table)
+    }
+    let hand_idx: Vec<u8> = hand.iter().map(card_index).collect();
+    let board_idx: Vec<u8> = board.iter().map(card_index).collect();
+    let seen = card_mask(&hand_idx) | card_mask(&board_idx) | card_mask(&dead.iter().map(card_index).collect::<Vec<u8>>());
+    let combos: Vec<[u8; 2]> = self.combos.iter().map(|h| [card_index(&h[0]), card_index(&h[1])]).collect();
+    // running total of weights of every combo an opponent can have, to pick one by weight
+    let mut cumulative = Vec::new();
+    for weights in ranges {
+      let mut acc = Vec::new();
+      let mut total = 0.0;
+      for (i, w) in weights.iter().enumerate() {
+        if *w > 0.0 && card_mask(&combos[i]) & seen == 0 {
+          total += w;
+          acc.push((i, total));
+        }
+      }
+      if acc.is_empty() {
+        return 0.0
+      }
+      cumulative.push(acc);
+    }
+
+    let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+    let n = board_idx.len();
+    let mut cards = [0u8; 7];
+    cards[2..2+n].copy_from_slice(&board_idx);
+    let mut opponents = vec![[0u8; 2]; ranges.len()];
+    let mut won = 0.0;
+    let mut dealt = 0;
+    // deals where opponents' combos share a card are thrown away, that keeps the joint weights right
+    for _ in 0..MULTIWAY_DEALS*10 {
+      if dealt == MULTIWAY_DEALS {
+        break;
+      }
+      let mut used = seen;
+      let mut overlap = false;
+      for (o, acc) in cumulative.iter().enumerate() {
+        let r = rng.gen::<f32>()*acc[acc.len()-1].1;
+        let k = acc.partition_point(|(_, c)| *c <= r).min(acc.len()-1);
+        let m = card_mask(&combos[acc[k].0]);
+        if used & m != 0 {
+          overlap = true;
+          break;
+        }
+        used |= m;
+        opponents[o] = combos[acc[k].0];
+      }
+      if overlap {
+        continue;
+      }
+      let mut deck: Vec<u8> = (0..NUM_CARDS as u8).filter(|c| used & (1 << c) == 0).collect();
+      let (runout, _) = deck.partial_shuffle(&mut rng, 5-n);
+      cards[2+n..].copy_from_slice(runout);
+      cards[0..2].copy_from_slice(&hand_idx);
+      let hero_score = score_table.best_score(&cards);
+      let mut best = hero_score;
+      let mut num_best = 1;
+      for combo in &opponents {
+        cards[0..2].copy_from_slice(combo);
+        let score = score_table.best_score(&cards);
+        if score > best {
+          best = score;
+          num_best = 1;
+        } else if score == best {
+          num_best += 1;
+        }
+      }
+      if hero_score == best {
+        won += 1.0/num_best as f32;
+      }
+      dealt += 1;
+    }
+    if dealt == 0 {
+      return 0.0
+    }
+    return won/dealt as f32
+  }
+
+  // share of the range (by weight) that is left after narrowing, compared to any two cards
+  pub fn range_share(&self, weights: &[f32], dead_cards: &[Card]) -> f32 {
+    let full: f32 = self.full_range(dead_cards).iter().sum();
+    return weights.iter().sum::<f32>()/full
+  }
+}
+
+// share of values below v in a sorted list, equal values count half
+fn percentile(sorted: &[f32], v: f32) -> f32 {
+  if sorted.is_empty() {
+    return 0.0
+  }
+  let below = sorted.partition_point(|x| *x < v);
+  let not_above = sorted.partition_point(|x| *x <= v);
+  return (below as f32 + (not_above-below) as f32*0.5)/sorted.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::tests::holdem_score_table;
+
+  #[test]
+  fn draws_have_equity_against_a_range() {
+    let score_table = holdem_score_table();
+    let villain = vec![conv_string_to_cards("2s 2d"), conv_string_to_cards("Qs Qd")];
+    let model = RangeModel{combos: villain, preflop_strength: vec![0.5, 0.99]};
+    let hand = conv_string_to_cards("Ah Kh");
+    let board = conv_string_to_cards("Qh Jh 2c");
+    // behind a set on the flop, but a flush and straight draw wins often by the river
+    let vs_set = model.equity_vs_range(&hand, &board, &[], &[1.0, 0.0], score_table);
+    assert!(vs_set > 0.25 && vs_set < 0.5, "{}", vs_set);
+    // dead cards are not dealt: with every other heart dead there is no flush
+    let dead = conv_string_to_cards("3h 4h 5h 6h 7h 8h 9h Th");
+    let no_flush = model.equity_vs_range(&hand, &board, &dead, &[1.0, 0.0], score_table);
+    assert!(no_flush < vs_set, "{} {}", no_flush, vs_set);
+    // on the river only made hands count, each combo by its weight: ace high loses to the set and beats queen high
+    let model = RangeModel{combos: vec![conv_string_to_cards("2s 2d"), conv_string_to_cards("Qs Jd")], preflop_strength: vec![0.5, 0.6]};
+    let river = conv_string_to_cards("Th 9h 2c 3s 4d");
+    assert_eq!(model.equity_vs_range(&hand, &river, &[], &[1.0, 3.0], score_table), 0.75);
+  }
+
+  #[test]
+  fn multiway_deal_scores_all_hands_together() {
+    let score_table = holdem_score_table();
+    let combos = vec![conv_string_to_cards("2s 2d"), conv_string_to_cards("Qs Jd"), conv_string_to_cards("Qc Jc")];
+    let model = RangeModel{combos: combos, preflop_strength: vec![0.5, 0.6, 0.6]};
+    let hand = conv_string_to_cards("Ah Kh");
+    let river = conv_string_to_cards("Th 9h 2c 3s 4d");
+    // hero has to beat both hands at once, not each one separately: equities don't multiply
+    assert_eq!(model.equity_vs_ranges(&hand, &river, &[], &[vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]], score_table), 1.0);
+    assert_eq!(model.equity_vs_ranges(&hand, &river, &[], &[vec![0.0, 1.0, 0.0], vec![1.0, 0.0, 0.0]], score_table), 0.0);
+    // both opponents can't hold the same combo, so the second one always has the set
+    let eq = model.equity_vs_ranges(&hand, &river, &[], &[vec![1.0, 1.0, 0.0], vec![1.0, 0.0, 0.0]], score_table);
+    assert_eq!(eq, 0.0);
+    // queen high for one and a set or queen high for the other, by weight
+    let eq = model.equity_vs_ranges(&hand, &river, &[], &[vec![0.0, 1.0, 0.0], vec![1.0, 0.0, 3.0]], score_table);
+    assert!((eq - 0.75).abs() < 0.02, "{}", eq);
+  }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Error;
+use crate::{Card, TablePosition, calculate_ev, get_hand_equity_and_opponent_range, is_in_position_range};
+use crate::hand_history::{HandHistory, ActionKind, Street};
+use crate::player_stats::PlayerStatsDb;
+use crate::range_narrowing::{RangeModel, tendencies};
+use crate::score_table::ScoreTable;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Mistake {
+  // called with less equity than the price the pot was laying
+  CallBelowPotOdds,
+  // folded to a bet with enough equity to call
+  FoldWithEquity,
+  // first raise preflop with a hand outside of the position's opening range
+  OpenOutsideRange,
+}
+
+impl fmt::Display for Mistake {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Mistake::CallBelowPotOdds => write!(f, "call below pot odds"),
+      Mistake::FoldWithEquity => write!(f, "fold with equity"),
+      Mistake::OpenOutsideRange => write!(f, "open outside range"),
+    }
+  }
+}
+
+pub struct Decision {
+  pub hand_id: String,
+  pub street: Street,
+  pub position: Option<TablePosition>,
+  pub hand: Vec<Card>,
+  pub board: Vec<Card>,
+  pub action: ActionKind,
+  // pot before hero acted and what hero had to put in to continue
+  pub total_pot: f32,
+  pub call_amount: f32,
+  pub equity: f32,
+  // equity is against opponent's range narrowed by their actions, not any two cards
+  pub narrowed: bool,
+  pub mistake: Mistake,
+  // for opens outside the range this is only a heuristic, see review_hand
+  pub ev_lost: f32,
+}
+
+impl fmt::Display for Decision {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let position = match self.position {
+      Some(p) => p.to_string(),
+      None => "BB".to_string(),
+    };
+    write!(f, "#{} {} {} {:?}", self.hand_id, self.street, position, self.hand)?;
+    if !self.board.is_empty() {
+      write!(f, " {:?}", self.board)?;
+    }
+    write!(f, ": {}", self.mistake)?;
+    match self.mistake {
+      Mistake::OpenOutsideRange => write!(f, ", AvgEq: {:.1}%", self.equity*100.0)?,
+      _ => write!(f, ", ${:.2} to call into ${:.2}, equity {:.1}% (need {:.1}%)", self.call_amount, self.total_pot,
+        self.equity*100.0, pot_odds(self.total_pot, self.call_amount)*100.0)?,
+    }
+    if self.narrowed {
+      write!(f, " vs narrowed range")?;
+    }
+    match self.mistake {
+      Mistake::OpenOutsideRange => write!(f, ", EV lost (heuristic): ${:.2}", self.ev_lost),
+      _ => write!(f, ", EV lost: ${:.2}", self.ev_lost),
+    }
+  }
+}
+
+pub struct SessionReview {
+  pub num_hands: usize,
+  pub num_decisions: usize,
+  // sorted by EV lost, biggest leak first
+  pub mistakes: Vec<Decision>,
+}
+
+impl fmt::Display for SessionReview {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let total_lost = self.mistakes.iter().fold(0.0, |acc, d| acc + d.ev_lost);
+    let heuristic_lost: f32 = self.mistakes.iter().filter(|d| d.mistake == Mistake::OpenOutsideRange).map(|d| d.ev_lost).sum();
+    write!(f, "Reviewed {} hands, {} hero decisions, {} mistakes, EV lost: ${:.2}", self.num_hands, self.num_decisions, self.mistakes.len(), total_lost)?;
+    if heuristic_lost > 0.0 {
+      write!(f, " (${:.2} of it is a heuristic for opens outside range)", heuristic_lost)?;
+    }
+    for mistake in [Mistake::CallBelowPotOdds, Mistake::FoldWithEquity, Mistake::OpenOutsideRange] {
+      let of_kind: Vec<&Decision> = self.mistakes.iter().filter(|d| d.mistake == mistake).collect();
+      if !of_kind.is_empty() {
+        let lost: f32 = of_kind.iter().map(|d| d.ev_lost).sum();
+        write!(f, "\n{:<20}:{} (${:.2})", mistake.to_string(), of_kind.len(), lost)?;
+      }
+    }
+    for d in &self.mistakes {
+      write!(f, "\n{}", d)?;
+    }
+    Ok(())
+  }
+}
+
+// equity needed to call, using the same model as calculate_ev(): we play for the pot including our call
+fn pot_odds(total_pot: f32, call_amount: f32) -> f32 {
+  return call_amount/(total_pot+call_amount)
+}
+
+// hero's equity on a street: average preflop equity of the hand, or equity against every opponent combo after the flop
+fn street_equity(hand: &Vec<Card>, board: &Vec<Card>, combinations: &HashMap<Vec<Card>, (f32,f32)>,
+  starting_hands: &HashMap<Vec<Card>, (f32,f32,f32)>, card_deck: &Vec<Card>) -> f32 {
+  if board.is_empty() {
+    let (_, avg_eq, _) = starting_hands[hand];
+    return avg_eq
+  }
+  let (_, real_my_hand_eq, _, _, _) = get_hand_equity_and_opponent_range(hand, board, combinations, starting_hands, card_deck);
+  return real_my_hand_eq
+}
+
+// lookup tables and models every hand of a session is reviewed with
+pub struct ReviewContext<'a> {
+  pub combinations: &'a HashMap<Vec<Card>, (f32,f32)>,
+  pub starting_hands: &'a HashMap<Vec<Card>, (f32,f32,f32)>,
+  pub card_deck: &'a Vec<Card>,
+  pub stats_db: &'a PlayerStatsDb,
+  pub range_model: &'a RangeModel,
+  pub score_table: &'a ScoreTable,
+}
+
+fn review_hand(hh: &HandHistory, ctx: &ReviewContext, decisions: &mut Vec<Decision>) -> Result<usize, Error> {
+  let hero = match &hh.hero {
+    Some(h) => h.clone(),
+    None => return Ok(0),
+  };
+  let mut hand = match hh.hero_cards() {
+    Some(cards) if cards.len() == 2 => cards.clone(),
+    _ => return Ok(0),
+  };
+  hand.sort();
+  let position = hh.position_of(&hero);
+
+  let mut num_decisions = 0;
+  let mut total_pot = 0.0;
+  let mut street = Street::Preflop;
+  let mut committed: HashMap<&str, f32> = HashMap::new();
+  let mut max_bet = 0.0;
+  let mut pot_opened = false;
+  let mut equities: HashMap<Street, f32> = HashMap::new();
+  let mut folded: Vec<&str> = Vec::new();
+  for (action_idx, action) in hh.actions.iter().enumerate() {
+    if action.street != street {
+      street = action.street;
+      committed.clear();
+      max_bet = 0.0;
+    }
+
+    let voluntary = !matches!(action.kind, ActionKind::Ante | ActionKind::SmallBlind | ActionKind::BigBlind | ActionKind::Return);
+    if action.player == hero && voluntary {
+      num_decisions += 1;
+      let board = hh.board_on(street);
+      // heads up after the flop we know enough about the opponent to narrow their range
+      let opponents: Vec<&str> = hh.seats.iter().map(|s| s.player.as_str())
+        .filter(|p| *p != hero && !folded.contains(p) && hh.actions.iter().any(|a| a.player == *p)).collect();
+      let narrowed = street != Street::Preflop && opponents.len() == 1;
+      let equity = if narrowed {
+        let villain = opponents[0];
+        let t = tendencies(hh.position_of(villain), ctx.stats_db.get(villain)?.as_ref());
+        let mut dead_cards = hand.clone();
+        dead_cards.extend(board.iter());
+        let weights = ctx.range_model.narrow(hh, villain, action_idx, &dead_cards, &t, ctx.score_table);
+        ctx.range_model.equity_vs_range(&hand, &board, &[], &weights, ctx.score_table)
+      } else {
+        *equities.entry(street).or_insert_with(|| street_equity(&hand, &board, ctx.combinations, ctx.starting_hands, ctx.card_deck))
+      };
+      let call_amount = max_bet - *committed.get(hero.as_str()).unwrap_or(&0.0);
+      let mut found = None;
+      match action.kind {
+        ActionKind::Call => {
+          let ev = calculate_ev(total_pot+action.amount, action.amount, equity);
+          if ev < 0.0 {
+            found = Some((Mistake::CallBelowPotOdds, action.amount, -ev));
+          }
+        },
+        ActionKind::Fold if call_amount > 0.0 => {
+          let ev = calculate_ev(total_pot+call_amount, call_amount, equity);
+          if ev > 0.0 {
+            found = Some((Mistake::FoldWithEquity, call_amount, ev));
+          }
+        },
+        ActionKind::Raise if street == Street::Preflop && !pot_opened => {
+          if let Some(pos) = position {
+            if !is_in_position_range(pos, &hand) {
+              // there is no EV model for raising preflop, so this is a heuristic: the open is treated as a call
+              // of the same size against one random hand with the hand's average equity
+              let ev = calculate_ev(total_pot+action.amount, action.amount, equity);
+              found = Some((Mistake::OpenOutsideRange, action.amount, if ev < 0.0 { -ev } else { 0.0 }));
+            }
+          }
+        },
+        _ => {},
+      }
+      if let Some((mistake, amount, ev_lost)) = found {
+        decisions.push(Decision {
+          hand_id: hh.hand_id.clone(),
+          street: street,
+          position: position,
+          hand: hand.clone(),
+          board: board,
+          action: action.kind,
+          total_pot: total_pot,
+          call_amount: amount,
+          equity: equity,
+          narrowed: narrowed,
+          mistake: mistake,
+          ev_lost: ev_lost,
+        });
+      }
+    }
+
+    if action.kind == ActionKind::Fold {
+      folded.push(action.player.as_str());
+    }
+    if voluntary && action.kind != ActionKind::Fold && action.kind != ActionKind::Check {
+      pot_opened = true;
+    }
+    if action.kind == ActionKind::Return {
+      total_pot -= action.amount;
+    } else {
+      total_pot += action.amount;
+    }
+    if action.kind != ActionKind::Ante {
+      committed.insert(action.player.as_str(), action.to);
+      if action.to > max_bet {
+        max_bet = action.to;
+      }
+    }
+  }
+  return Ok(num_decisions)
+}
+
+// replays every hero decision in the hand histories and collects the ones that lost EV
+pub fn review_session(hands: &Vec<HandHistory>, ctx: &ReviewContext) -> Result<SessionReview, Error> {
+  let mut mistakes = Vec::new();
+  let mut num_decisions = 0;
+  for hh in hands {
+    num_decisions += review_hand(hh, ctx, &mut mistakes)?;
+  }
+  mistakes.sort_by(|a, b| b.ev_lost.partial_cmp(&a.ev_lost).unwrap());
+  return Ok(SessionReview{num_hands: hands.len(), num_decisions: num_decisions, mistakes: mistakes})
+}
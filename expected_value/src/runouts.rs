@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use crate::{Card, HandRank};
+use crate::contribution::hero_hand_rank;
+use crate::score_table::{ScoreTable, card_index};
+
+pub struct RankOdds {
+  // runouts where hero ends up with this rank on the river
+  pub num_runouts: u32,
+  // sum over those runouts of the share of opponent combos that still beat hero
+  pub beaten_share: f64,
+}
+
+// Exact odds of how hero's hand ends up on the river, enumerating every remaining turn and river card.
+// on the river there are no runouts and every probability is zero.
+pub struct RunoutOdds {
+  pub num_runouts: u32,
+  pub current_rank: HandRank,
+  pub final_ranks: HashMap<HandRank, RankOdds>,
+}
+
+impl RunoutOdds {
+  // chance of ending up with this rank by the river
+  pub fn probability(&self, rank: &HandRank) -> f32 {
+    return match self.final_ranks.get(rank) {
+      Some(odds) => odds.num_runouts as f32/self.num_runouts as f32,
+      None => 0.0,
+    }
+  }
+
+  // chance of ending up with this rank by the river and still losing to opponent's hand
+  pub fn probability_behind(&self, rank: &HandRank) -> f32 {
+    return match self.final_ranks.get(rank) {
+      Some(odds) => (odds.beaten_share/self.num_runouts as f64) as f32,
+      None => 0.0,
+    }
+  }
+
+  pub fn improved_ranks(&self) -> Vec<HandRank> {
+    let mut ranks: Vec<HandRank> = self.final_ranks.keys().filter(|r| **r > self.current_rank).cloned().collect();
+    ranks.sort();
+    return ranks
+  }
+
+  // chance to improve to a better hand class and be ahead of opponent on the river
+  pub fn improve_and_win_probability(&self) -> f32 {
+    let mut p = 0.0;
+    for rank in self.improved_ranks() {
+      p += self.probability(&rank) - self.probability_behind(&rank);
+    }
+    return p
+  }
+
+  // chance to improve to a better hand class but still lose to a better opponent hand
+  pub fn improve_and_lose_probability(&self) -> f32 {
+    let mut p = 0.0;
+    for rank in self.improved_ranks() {
+      p += self.probability_behind(&rank);
+    }
+    return p
+  }
+}
+
+// How much more we need to win on later streets when we hit, for a call to break even.
+// calculate_ev() is pot*win_ch - call, so break even is (pot+future)*win_ch = call.
+pub fn implied_odds_needed(total_pot: f32, call_amount: f32, win_ch: f32) -> f32 {
+  if win_ch <= 0.0 {
+    return f32::INFINITY
+  }
+  let needed = call_amount/win_ch - total_pot;
+  if needed < 0.0 {
+    return 0.0
+  }
+  return needed
+}
+
+// Expected amount lost on later streets when we hit our hand but opponent still has better one.
+// Assumes we pay off one more bet of future_bet size in those cases.
+pub fn reverse_implied_odds(future_bet: f32, lose_ch: f32) -> f32 {
+  return future_bet * lose_ch
+}
+
+// share of opponent combos, from cards not seen yet, that beat hero on a complete board
+fn beaten_share(hero_score: f32, board: &[u8; 5], remaining: &[u8], score_table: &ScoreTable) -> f64 {
+  let mut beaten = 0;
+  let mut total = 0;
+  let mut opp_cards = [0, 0, board[0], board[1], board[2], board[3], board[4]];
+  for i in 0..remaining.len() {
+    if board.contains(&remaining[i]) {
+      continue;
+    }
+    for j in (i+1)..remaining.len() {
+      if board.contains(&remaining[j]) {
+        continue;
+      }
+      opp_cards[0] = remaining[i];
+      opp_cards[1] = remaining[j];
+      if score_table.best_score(&opp_cards) > hero_score {
+        beaten += 1;
+      }
+      total += 1;
+    }
+  }
+  return beaten as f64/total as f64
+}
+
+pub fn calculate_runout_odds(hand: &Vec<Card>, community: &Vec<Card>, card_deck: &[Card], combinations: &HashMap<Vec<Card>,(f32,f32)>, score_table: &ScoreTable) -> RunoutOdds {
+  let current_rank = hero_hand_rank(hand, community, combinations);
+  // nothing to come on the river
+  if community.len() == 5 {
+    return RunoutOdds{num_runouts: 0, current_rank: current_rank, final_ranks: HashMap::new()}
+  }
+  let mut remaining_deck = card_deck.to_vec();
+  remaining_deck.retain(|x| !hand.contains(x) && !community.contains(x));
+  let remaining: Vec<u8> = remaining_deck.iter().map(card_index).collect();
+
+  // all turn+river pairs on the flop, all river cards on the turn
+  let cards_to_come = 5-community.len();
+  let runouts: Vec<Vec<Card>> = match cards_to_come {
+    1 => remaining_deck.iter().map(|c| vec![*c]).collect(),
+    2 => {
+      let mut res = Vec::new();
+      for i in 0..remaining_deck.len() {
+        for j in (i+1)..remaining_deck.len() {
+          res.push(vec![remaining_deck[i], remaining_deck[j]]);
+        }
+      }
+      res
+    },
+    _ => panic!("unexpected community len {} in calculate_runout_odds", community.len()),
+  };
+
+  let mut odds = RunoutOdds{num_runouts: 0, current_rank: current_rank, final_ranks: HashMap::new()};
+  for runout in runouts {
+    let mut board = community.clone();
+    board.extend(runout.iter());
+    // only count hands that hero makes with hole cards, board improving on its own is not our draw
+    let rank = hero_hand_rank(hand, &board, combinations);
+    let mut board_indices = [0u8; 5];
+    for i in 0..5 {
+      board_indices[i] = card_index(&board[i]);
+    }
+    let entry = odds.final_ranks.entry(rank).or_insert(RankOdds{num_runouts: 0, beaten_share: 0.0});
+    entry.num_runouts += 1;
+    // showdown against opponent only matters for the hands we are drawing to
+    if rank > current_rank {
+      let hero_score = score_table.best_score(&[card_index(&hand[0]), card_index(&hand[1]),
+        board_indices[0], board_indices[1], board_indices[2], board_indices[3], board_indices[4]]);
+      entry.beaten_share += beaten_share(hero_score, &board_indices, &remaining, score_table);
+    }
+    odds.num_runouts += 1;
+  }
+  return odds
+}
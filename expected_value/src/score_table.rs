@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use crate::{Card, CardSuit};
+
+pub const NUM_CARDS: usize = 52;
+const NUM_FIVE_CARD_HANDS: usize = 2598960;
+
+// card index is [0..52), ordered the same way as Card's Ord: by rank first, then by suit.
+// so sorted Vec<Card> always converts into sorted indices.
+pub fn card_index(card: &Card) -> u8 {
+  return (card.rank-2)*4 + (card.suit as u8 - 1)
+}
+
+pub fn index_to_card(idx: u8) -> Card {
+  let suit = match idx % 4 {
+    0 => CardSuit::Heart,
+    1 => CardSuit::Spade,
+    2 => CardSuit::Club,
+    _ => CardSuit::Diamond,
+  };
+  return Card{rank: idx/4+2, suit: suit}
+}
+
+// Dense copy of combinations map.
+// Looking up Vec<Card> key in the HashMap is fine for a handful of hands, but anything that enumerates
+// millions of boards spends all of its time hashing. Here every sorted 5 card hand is converted
+// into its position in combinatorial number system, which is a plain index into Vec.
+pub struct ScoreTable {
+  binomials: [[u32; 6]; NUM_CARDS+1],
+  scores: Vec<f32>,
+}
+
+impl ScoreTable {
+  pub fn new(combinations: &HashMap<Vec<Card>,(f32,f32)>) -> ScoreTable {
+    let mut binomials = [[0u32; 6]; NUM_CARDS+1];
+    binomials[0][0] = 1;
+    for n in 1..(NUM_CARDS+1) {
+      binomials[n][0] = 1;
+      for k in 1..6 {
+        binomials[n][k] = binomials[n-1][k-1] + binomials[n-1][k];
+      }
+    }
+    let mut table = ScoreTable{binomials: binomials, scores: vec![0.0; NUM_FIVE_CARD_HANDS]};
+    for (k, (score, _)) in combinations {
+      let mut hand = [0u8; 5];
+      for i in 0..5 {
+        hand[i] = card_index(&k[i]);
+      }
+      hand.sort();
+      let idx = table.hand_index(&hand);
+      table.scores[idx] = *score;
+    }
+    return table
+  }
+
+  // cards must be sorted
+  fn hand_index(&self, cards: &[u8; 5]) -> usize {
+    let mut idx = 0;
+    for (i, card) in cards.iter().enumerate() {
+      idx += self.binomials[*card as usize][i+1];
+    }
+    return idx as usize
+  }
+
+  // cards must be sorted
+  pub fn score5(&self, cards: &[u8; 5]) -> f32 {
+    return self.scores[self.hand_index(cards)]
+  }
+
+  // best score of any 5 card subset. works for 5, 6 and 7 cards in any order.
+  // unlike get_best_hand this does not require hole cards to be part of the hand, so board can play.
+  pub fn best_score(&self, cards: &[u8]) -> f32 {
+    let mut sorted = [0u8; 7];
+    let n = cards.len();
+    sorted[..n].copy_from_slice(cards);
+    sorted[..n].sort_unstable();
+    let mut best = 0.0;
+    for a in 0..n {
+      for b in (a+1)..n {
+        for c in (b+1)..n {
+          for d in (c+1)..n {
+            for e in (d+1)..n {
+              let score = self.score5(&[sorted[a], sorted[b], sorted[c], sorted[d], sorted[e]]);
+              if score > best {
+                best = score;
+              }
+            }
+          }
+        }
+      }
+    }
+    return best
+  }
+
+  pub fn best_score_cards(&self, cards: &[Card]) -> f32 {
+    let indices: Vec<u8> = cards.iter().map(card_index).collect();
+    return self.best_score(&indices)
+  }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use std::sync::OnceLock;
+  use crate::conv_string_to_cards;
+
+  // hold'em score of 5 sorted card indices on the same scale as hands.csv: hundreds are the hand class,
+  // the rest are ranks that break ties inside it, in base 15
+  fn holdem_score5(cards: &[u8; 5]) -> f32 {
+    let mut counts = [0u8; 15];
+    for c in cards {
+      counts[(c/4+2) as usize] += 1;
+    }
+    // by count, then by rank: quads before kicker, trips before pair
+    let mut ranks: Vec<u8> = (2..15u8).filter(|r| counts[*r as usize] > 0).collect();
+    ranks.sort_by(|a, b| counts[*b as usize].cmp(&counts[*a as usize]).then(b.cmp(a)));
+    let is_flush = cards.iter().all(|c| c%4 == cards[0]%4);
+    let mut straight_high = None;
+    if ranks.len() == 5 {
+      if ranks[0] - ranks[4] == 4 {
+        straight_high = Some(ranks[0]);
+      } else if ranks == vec![14, 5, 4, 3, 2] {
+        // wheel, ace plays low
+        straight_high = Some(5);
+      }
+    }
+    let top = counts[ranks[0] as usize];
+    let second = ranks.get(1).map(|r| counts[*r as usize]).unwrap_or(0);
+    let class = match (straight_high, is_flush, top, second) {
+      (Some(14), true, _, _) => 9,
+      (Some(_), true, _, _) => 8,
+      (_, _, 4, _) => 7,
+      (_, _, 3, 2) => 6,
+      (_, true, _, _) => 5,
+      (Some(_), _, _, _) => 4,
+      (_, _, 3, _) => 3,
+      (_, _, 2, 2) => 2,
+      (_, _, 2, _) => 1,
+      _ => 0,
+    };
+    let tiebreak = match straight_high {
+      Some(high) => vec![high],
+      None => ranks,
+    };
+    let mut kickers = 0.0;
+    let mut scale = 1.0;
+    for r in tiebreak {
+      scale /= 15.0;
+      kickers += r as f32*scale;
+    }
+    return class as f32*100.0 + kickers*100.0
+  }
+
+  // full 52 card hold'em score table for tests, so they don't need the hand files. built once
+  pub fn holdem_score_table() -> &'static ScoreTable {
+    static TABLE: OnceLock<ScoreTable> = OnceLock::new();
+    return TABLE.get_or_init(|| {
+      let mut table = ScoreTable::new(&HashMap::new());
+      let n = NUM_CARDS as u8;
+      for a in 0..n {
+        for b in (a+1)..n {
+          for c in (b+1)..n {
+            for d in (c+1)..n {
+              for e in (d+1)..n {
+                let hand = [a, b, c, d, e];
+                let idx = table.hand_index(&hand);
+                table.scores[idx] = holdem_score5(&hand);
+              }
+            }
+          }
+        }
+      }
+      table
+    })
+  }
+
+  fn best(s: &str) -> f32 {
+    return holdem_score_table().best_score_cards(&conv_string_to_cards(s))
+  }
+
+  #[test]
+  fn board_can_play() {
+    // both players play the broadway straight on the board
+    assert_eq!(best("2h 3d Ah Kd Qc Js Th"), best("4c 5c Ah Kd Qc Js Th"));
+    assert!(best("5h 4d Ah 2c 3s 9d Kh") > best("Ac Ad Ah 2c 3s 9d Kh"));
+  }
+
+  #[test]
+  fn hand_classes() {
+    assert!(best("Ah 2h 3h 4h 5h") < best("2h 3h 4h 5h 6h"));
+    assert!(best("Ks Kd Kc Qh Qd") > best("Ah Kh Qh Jh 9h"));
+    assert!(best("Ah Kh Qh Jh 9h") > best("Ac Kd Qh Js Th"));
+    assert!(best("Ah Ad 9c 9d 2s") > best("Kh Kd Qc Qd Js"));
+  }
+}
@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::SystemTime;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use crate::bots::{BotContext, BotKind, create_bot};
+use crate::game_engine::{Agent, GameConfig, Table};
+use crate::score_table::ScoreTable;
+
+const HANDS_PER_BATCH: usize = 1000;
+
+// running sums of a bot's results in big blinds per hand
+#[derive(Clone, Copy, Default)]
+pub struct BotResult {
+  pub hands: u64,
+  pub sum: f64,
+  pub sum_sq: f64,
+}
+
+impl BotResult {
+  fn add(&mut self, other: &BotResult) {
+    self.hands += other.hands;
+    self.sum += other.sum;
+    self.sum_sq += other.sum_sq;
+  }
+
+  pub fn bb_per_100(&self) -> f64 {
+    if self.hands == 0 {
+      return 0.0
+    }
+    return self.sum/self.hands as f64*100.0
+  }
+
+  // half width of the 95% confidence interval of bb/100
+  pub fn confidence_95(&self) -> f64 {
+    if self.hands < 2 {
+      return f64::INFINITY
+    }
+    let n = self.hands as f64;
+    let mean = self.sum/n;
+    let variance = (self.sum_sq - n*mean*mean)/(n-1.0);
+    return 1.96*(variance.max(0.0)/n).sqrt()*100.0
+  }
+}
+
+// Cash game harness: plays num_hands between the bots, one seat each. Stacks don't carry over, every seat is topped
+// up to stack_bb before each hand like auto top-up at a cash table, so hands are independent and results can be
+// averaged. Button rotates through all seats.
+pub fn run_self_play(bots: &[BotKind], num_hands: usize, stack_bb: u32, config: GameConfig, ctx: &BotContext, score_table: &ScoreTable) -> Vec<BotResult> {
+  let num_threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  let results = Mutex::new(vec![BotResult::default(); bots.len()]);
+  let hands_done = AtomicUsize::new(0);
+  let seed = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+
+  thread::scope(|s| {
+    for thread_idx in 0..num_threads {
+      let results = &results;
+      let hands_done = &hands_done;
+      s.spawn(move || {
+        let mut rng = ChaCha20Rng::seed_from_u64(seed + thread_idx as u64);
+        let mut agents: Vec<Box<dyn Agent + '_>> = bots.iter().enumerate()
+          .map(|(i, kind)| create_bot(*kind, ctx, ChaCha20Rng::seed_from_u64(seed + (thread_idx*bots.len() + i) as u64 + 1000)))
+          .collect();
+        let mut table = Table::new(config, vec![stack_bb*config.big_blind; bots.len()], ctx.card_deck);
+        table.button = thread_idx % bots.len();
+        loop {
+          let start = hands_done.fetch_add(HANDS_PER_BATCH, Ordering::SeqCst);
+          if start >= num_hands {
+            break;
+          }
+          let mut batch = vec![BotResult::default(); bots.len()];
+          for _ in start..(start+HANDS_PER_BATCH).min(num_hands) {
+            // top up, nobody busts or builds a deeper stack
+            table.stacks = vec![stack_bb*config.big_blind; bots.len()];
+            let result = table.play_hand(&mut agents, score_table, &mut rng);
+            for (seat, net) in result.net.iter().enumerate() {
+              let bb = *net as f64/config.big_blind as f64;
+              batch[seat].hands += 1;
+              batch[seat].sum += bb;
+              batch[seat].sum_sq += bb*bb;
+            }
+          }
+          let mut results = results.lock().unwrap();
+          for seat in 0..bots.len() {
+            results[seat].add(&batch[seat]);
+          }
+        }
+      });
+    }
+  });
+  return results.into_inner().unwrap()
+}
+
+pub fn print_self_play_results(bots: &[BotKind], results: &[BotResult], stack_bb: u32) {
+  println!("Cash game, stacks topped up to {}bb every hand", stack_bb);
+  for (seat, kind) in bots.iter().enumerate() {
+    let r = &results[seat];
+    println!("{:<20}:{:+.2} bb/100 (+/- {:.2}, {} hands)", format!("{} (seat {})", kind, seat+1), r.bb_per_100(), r.confidence_95(), r.hands);
+  }
+}
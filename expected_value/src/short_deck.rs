@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use itertools::Itertools;
+use crate::{Card, HandRank};
+use crate::score_table::{NUM_CARDS, index_to_card};
+use crate::variant::Variant;
+
+// Short deck (6+) hold'em: 36 cards from sixes up, A-6-7-8-9 is the lowest straight,
+// and with fewer cards of each suit a flush is harder to make than a full house, so it ranks above it.
+pub const SHORT_DECK_ORDER: [HandRank; 10] = [
+  HandRank::HighCard,
+  HandRank::Pair,
+  HandRank::TwoPairs,
+  HandRank::ThreeOfAKind,
+  HandRank::Straight,
+  HandRank::FullHouse,
+  HandRank::Flush,
+  HandRank::FourOfAKind,
+  HandRank::StraightFlush,
+  HandRank::RoyalFlush,
+];
+
+// position of the hand class in short deck order, higher is better
+fn short_deck_strength(rank: HandRank) -> usize {
+  return SHORT_DECK_ORDER.iter().position(|r| *r == rank).unwrap()
+}
+
+// hand class and ranks that break ties inside it, most important first
+fn evaluate5(cards: &[Card]) -> (HandRank, Vec<u8>) {
+  let mut counts: HashMap<u8, u8> = HashMap::new();
+  for c in cards {
+    *counts.entry(c.rank).or_insert(0) += 1;
+  }
+  // by count, then by rank: quads before kicker, trips before pair
+  let mut groups: Vec<(u8, u8)> = counts.into_iter().map(|(rank, n)| (n, rank)).collect();
+  groups.sort_by(|a, b| b.cmp(a));
+  let ranks: Vec<u8> = groups.iter().map(|(_, r)| *r).collect();
+  let is_flush = cards.iter().all(|c| c.suit == cards[0].suit);
+  let mut straight_high = None;
+  if groups.len() == 5 {
+    if ranks[0] - ranks[4] == 4 {
+      straight_high = Some(ranks[0]);
+    } else if ranks == vec![14, 9, 8, 7, 6] {
+      // ace plays low below the six
+      straight_high = Some(9);
+    }
+  }
+  let rank = match (straight_high, is_flush, groups[0].0, groups.get(1).map(|g| g.0)) {
+    (Some(14), true, _, _) => HandRank::RoyalFlush,
+    (Some(_), true, _, _) => HandRank::StraightFlush,
+    (_, _, 4, _) => HandRank::FourOfAKind,
+    (_, true, _, _) => HandRank::Flush,
+    (_, _, 3, Some(2)) => HandRank::FullHouse,
+    (Some(_), _, _, _) => HandRank::Straight,
+    (_, _, 3, _) => HandRank::ThreeOfAKind,
+    (_, _, 2, Some(2)) => HandRank::TwoPairs,
+    (_, _, 2, _) => HandRank::Pair,
+    _ => HandRank::HighCard,
+  };
+  if let Some(high) = straight_high {
+    return (rank, vec![high])
+  }
+  return (rank, ranks)
+}
+
+// score on the same scale as hands.csv: hundreds are the hand class in short deck order, the rest breaks ties
+fn score5(cards: &[Card]) -> f32 {
+  let (rank, tiebreak) = evaluate5(cards);
+  let mut kickers = 0.0;
+  let mut scale = 1.0;
+  for r in tiebreak {
+    scale /= 15.0;
+    kickers += r as f32*scale;
+  }
+  return short_deck_strength(rank) as f32*100.0 + kickers*100.0
+}
+
+// best 5 out of 5, 6 or 7 cards. board can play, same as hold'em
+fn best_score(cards: &[Card]) -> f32 {
+  let mut best = 0.0;
+  for combo in cards.iter().cloned().combinations(5) {
+    let score = score5(&combo);
+    if score > best {
+      best = score;
+    }
+  }
+  return best
+}
+
+pub struct ShortDeck {}
+
+impl Variant for ShortDeck {
+  fn name(&self) -> String {
+    return "Short deck".to_string()
+  }
+
+  fn deck(&self) -> Vec<Card> {
+    return (0..NUM_CARDS as u8).map(index_to_card).filter(|c| c.rank >= 6).collect()
+  }
+
+  fn num_hole_cards(&self) -> usize {
+    return 2
+  }
+
+  fn board_streets(&self) -> Vec<usize> {
+    return vec![3, 1, 1]
+  }
+
+  fn score(&self, hole: &[Card], board: &[Card]) -> f32 {
+    let cards: Vec<Card> = hole.iter().chain(board.iter()).cloned().collect();
+    return best_score(&cards)
+  }
+
+  fn hand_order(&self) -> [HandRank; 10] {
+    return SHORT_DECK_ORDER
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+
+  fn score(s: &str) -> f32 {
+    return score5(&conv_string_to_cards(s))
+  }
+
+  #[test]
+  fn ace_plays_low_in_straight() {
+    let (rank, tiebreak) = evaluate5(&conv_string_to_cards("Ah 6s 7c 8d 9h"));
+    assert_eq!(rank, HandRank::Straight);
+    assert_eq!(tiebreak, vec![9]);
+    assert!(score("Ah 6s 7c 8d 9h") < score("6h 7s 8c 9d Th"));
+    assert!(score("Ah 6s 7c 8d 9h") > score("Ah As Ac Kd Qh"));
+  }
+
+  #[test]
+  fn flush_beats_full_house() {
+    assert_eq!(evaluate5(&conv_string_to_cards("Ah Jh 9h 7h 6h")).0, HandRank::Flush);
+    assert!(score("Ah Jh 9h 7h 6h") > score("Kh Ks Kc Qd Qh"));
+    assert!(score("6h 6s 6c 7d 7h") < score("8h 7h 6h Th Jh"));
+  }
+}
@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Instant;
+use crate::Card;
+use crate::preflop_matrix::{PreflopRange, NUM_CLASSES, class_combos};
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index, index_to_card};
+
+// Heads up subgame solver with CFR+ on a public tree.
+// Player 0 is out of position and acts first on every street. Every node keeps regrets and
+// strategy sums for all combos of the player to act, and a traversal works on whole range vectors.
+
+// values of a subtree for the traversing player given both players' reach
+type SubtreeValues<'a> = dyn FnMut(&Node, &[Vec<f32>; 2]) -> Vec<f32> + 'a;
+
+#[derive(Clone)]
+pub struct SolverConfig {
+  // pot at the start of the subgame and stack behind for both players
+  pub pot: f32,
+  pub effective_stack: f32,
+  // bet sizes as a share of the pot, e.g. 0.33, 0.75
+  pub bet_sizes: Vec<f32>,
+  // raise sizes as a share of the pot after calling
+  pub raise_sizes: Vec<f32>,
+  // bets plus raises allowed on one street, all-in is always possible
+  pub max_bets_per_street: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SolverAction {
+  Check,
+  Fold,
+  Call,
+  Bet(f32),
+  Raise(f32),
+  AllIn(f32),
+}
+
+impl fmt::Display for SolverAction {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SolverAction::Check => write!(f, "check"),
+      SolverAction::Fold => write!(f, "fold"),
+      SolverAction::Call => write!(f, "call"),
+      SolverAction::Bet(amount) => write!(f, "bet {:.1}", amount),
+      SolverAction::Raise(to) => write!(f, "raise {:.1}", to),
+      SolverAction::AllIn(amount) => write!(f, "all-in {:.1}", amount),
+    }
+  }
+}
+
+// hand strengths of both players on one complete board, sorted for the showdown sweep
+pub struct BoardStrengths {
+  strengths: [Vec<f32>; 2],
+  order: [Vec<usize>; 2],
+}
+
+pub struct Decision {
+  pub player: usize,
+  pub actions: Vec<SolverAction>,
+  pub children: Vec<Node>,
+  // [hand*num_actions + action]
+  regrets: Vec<f32>,
+  strategy_sum: Vec<f32>,
+}
+
+pub enum Node {
+  Decision(Decision),
+  Fold{folder: usize, contrib: [f32; 2]},
+  Showdown{contrib: [f32; 2], board: Rc<BoardStrengths>},
+  // one child for every card that can come
+  Chance{cards: Vec<u8>, children: Vec<Node>},
+}
+
+pub struct Subgame {
+  pub config: SolverConfig,
+  pub board: Vec<Card>,
+  pub hands: [Vec<[u8; 2]>; 2],
+  pub initial_reach: [Vec<f32>; 2],
+  // for every hand of a player, index of the same combo in the other player's range
+  same_combo: [Vec<Option<usize>>; 2],
+  pub root: Node,
+}
+
+// range classes expanded into combos that don't use board cards
+fn range_combos(range: &PreflopRange, board: &[u8]) -> (Vec<[u8; 2]>, Vec<f32>) {
+  let mut hands = Vec::new();
+  let mut weights = Vec::new();
+  for class in 0..NUM_CLASSES {
+    if range.weights[class] <= 0.0 {
+      continue;
+    }
+    for combo in class_combos(class) {
+      if board.contains(&combo[0]) || board.contains(&combo[1]) {
+        continue;
+      }
+      hands.push(combo);
+      weights.push(range.weights[class]);
+    }
+  }
+  return (hands, weights)
+}
+
+struct TreeBuilder<'a> {
+  config: &'a SolverConfig,
+  hands: &'a [Vec<[u8; 2]>; 2],
+  score_table: &'a ScoreTable,
+  boards: HashMap<Vec<u8>, Rc<BoardStrengths>>,
+}
+
+impl TreeBuilder<'_> {
+  fn board_strengths(&mut self, board: &[u8]) -> Rc<BoardStrengths> {
+    let mut key = board.to_vec();
+    key.sort();
+    if let Some(b) = self.boards.get(&key) {
+      return b.clone()
+    }
+    let mut strengths: [Vec<f32>; 2] = [Vec::new(), Vec::new()];
+    let mut order: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+    for p in 0..2 {
+      strengths[p] = self.hands[p].iter().map(|h| {
+        // hands that use a card from the runout can't be here, they never reach this board
+        if board.contains(&h[0]) || board.contains(&h[1]) {
+          return -1.0
+        }
+        let mut cards = vec![h[0], h[1]];
+        cards.extend(board.iter());
+        self.score_table.best_score(&cards)
+      }).collect();
+      let mut o: Vec<usize> = (0..self.hands[p].len()).collect();
+      o.sort_by(|a, b| strengths[p][*a].partial_cmp(&strengths[p][*b]).unwrap());
+      order[p] = o;
+    }
+    let b = Rc::new(BoardStrengths{strengths: strengths, order: order});
+    self.boards.insert(key, b.clone());
+    return b
+  }
+
+  // end of a street: showdown on the river, otherwise deal the next card
+  fn street_end(&mut self, board: &[u8], contrib: [f32; 2]) -> Node {
+    if board.len() == 5 {
+      return Node::Showdown{contrib: contrib, board: self.board_strengths(board)}
+    }
+    let all_in = contrib.iter().any(|c| *c >= self.config.effective_stack);
+    let mut cards = Vec::new();
+    let mut children = Vec::new();
+    for card in 0..NUM_CARDS as u8 {
+      if board.contains(&card) {
+        continue;
+      }
+      let mut next_board = board.to_vec();
+      next_board.push(card);
+      cards.push(card);
+      children.push(if all_in {
+        self.street_end(&next_board, contrib)
+      } else {
+        self.decision(&next_board, 0, contrib, 0, false)
+      });
+    }
+    return Node::Chance{cards: cards, children: children}
+  }
+
+  fn decision(&mut self, board: &Vec<u8>, player: usize, contrib: [f32; 2], num_bets: usize, checked: bool) -> Node {
+    let stack = self.config.effective_stack;
+    let pot = self.config.pot + contrib[0] + contrib[1];
+    let opp = 1-player;
+    let mut actions = Vec::new();
+    let mut children = Vec::new();
+    if contrib[player] == contrib[opp] {
+      actions.push(SolverAction::Check);
+      children.push(if checked || player == 1 {
+        self.street_end(board, contrib)
+      } else {
+        self.decision(board, opp, contrib, num_bets, true)
+      });
+      if num_bets < self.config.max_bets_per_street {
+        for size in &self.config.bet_sizes {
+          let amount = pot*size;
+          if contrib[player] + amount >= stack {
+            continue;
+          }
+          let mut next = contrib;
+          next[player] += amount;
+          actions.push(SolverAction::Bet(amount));
+          children.push(self.decision(board, opp, next, num_bets+1, false));
+        }
+      }
+      if contrib[player] < stack {
+        let mut next = contrib;
+        next[player] = stack;
+        actions.push(SolverAction::AllIn(stack-contrib[player]));
+        children.push(self.decision(board, opp, next, num_bets+1, false));
+      }
+    } else {
+      actions.push(SolverAction::Fold);
+      children.push(Node::Fold{folder: player, contrib: contrib});
+      let mut called = contrib;
+      called[player] = contrib[opp];
+      actions.push(SolverAction::Call);
+      children.push(self.street_end(board, called));
+      // can only raise when opponent isn't all-in yet
+      if contrib[opp] < stack {
+        if num_bets < self.config.max_bets_per_street {
+          let pot_after_call = self.config.pot + 2.0*contrib[opp];
+          for size in &self.config.raise_sizes {
+            let to = contrib[opp] + pot_after_call*size;
+            if to >= stack {
+              continue;
+            }
+            let mut next = contrib;
+            next[player] = to;
+            actions.push(SolverAction::Raise(to));
+            children.push(self.decision(board, opp, next, num_bets+1, false));
+          }
+        }
+        let mut next = contrib;
+        next[player] = stack;
+        actions.push(SolverAction::AllIn(stack-contrib[player]));
+        children.push(self.decision(board, opp, next, num_bets+1, false));
+      }
+    }
+    let size = self.hands[player].len()*actions.len();
+    return Node::Decision(Decision{player: player, actions: actions, children: children, regrets: vec![0.0; size], strategy_sum: vec![0.0; size]})
+  }
+}
+
+// opponent reach that isn't blocked by each of our hands
+fn unblocked_reach(hands: &[[u8; 2]], opp_reach: &[f32], opp_hands: &[[u8; 2]], same: &[Option<usize>]) -> Vec<f32> {
+  let mut total = 0.0;
+  let mut per_card = [0.0f32; NUM_CARDS];
+  for (o, h) in opp_hands.iter().enumerate() {
+    total += opp_reach[o];
+    per_card[h[0] as usize] += opp_reach[o];
+    per_card[h[1] as usize] += opp_reach[o];
+  }
+  return hands.iter().enumerate().map(|(i, h)| {
+    let same_reach = match same[i] { Some(o) => opp_reach[o], None => 0.0 };
+    total - per_card[h[0] as usize] - per_card[h[1] as usize] + same_reach
+  }).collect()
+}
+
+// regret matching+, uniform when there is no positive regret
+fn current_strategy(regrets: &[f32], num_actions: usize) -> Vec<f32> {
+  let num_hands = regrets.len()/num_actions;
+  let mut strategy = vec![0.0; regrets.len()];
+  for h in 0..num_hands {
+    let row = &regrets[h*num_actions..(h+1)*num_actions];
+    let total: f32 = row.iter().map(|r| r.max(0.0)).sum();
+    for a in 0..num_actions {
+      strategy[h*num_actions+a] = if total > 0.0 { row[a].max(0.0)/total } else { 1.0/num_actions as f32 };
+    }
+  }
+  return strategy
+}
+
+impl Decision {
+  pub fn average_strategy(&self) -> Vec<f32> {
+    return current_strategy(&self.strategy_sum, self.actions.len())
+  }
+}
+
+impl Subgame {
+  pub fn new(oop_range: &PreflopRange, ip_range: &PreflopRange, board: &[Card], config: SolverConfig, score_table: &ScoreTable) -> Subgame {
+    let board_idx: Vec<u8> = board.iter().map(card_index).collect();
+    let (hands0, reach0) = range_combos(oop_range, &board_idx);
+    let (hands1, reach1) = range_combos(ip_range, &board_idx);
+    let hands = [hands0, hands1];
+    let mut same_combo: [Vec<Option<usize>>; 2] = [Vec::new(), Vec::new()];
+    for p in 0..2 {
+      let index: HashMap<[u8; 2], usize> = hands[1-p].iter().enumerate().map(|(i, h)| (*h, i)).collect();
+      same_combo[p] = hands[p].iter().map(|h| index.get(h).cloned()).collect();
+    }
+    let root = {
+      let mut builder = TreeBuilder{config: &config, hands: &hands, score_table: score_table, boards: HashMap::new()};
+      builder.decision(&board_idx, 0, [0.0, 0.0], 0, false)
+    };
+    return Subgame{config: config, board: board.to_vec(), hands: hands, initial_reach: [reach0, reach1], same_combo: same_combo, root: root}
+  }
+
+  // what traverser wins with each hand at a terminal, relative to what they put into the subgame
+  fn terminal_values(&self, node: &Node, p: usize, opp_reach: &[f32]) -> Vec<f32> {
+    let opp = 1-p;
+    let pot = self.config.pot;
+    match node {
+      Node::Fold{folder, contrib} => {
+        let payoff = if *folder == p { -contrib[p] } else { pot + contrib[opp] };
+        return unblocked_reach(&self.hands[p], opp_reach, &self.hands[opp], &self.same_combo[p]).iter().map(|r| r*payoff).collect()
+      },
+      Node::Showdown{contrib, board} => {
+        let win_payoff = pot + contrib[opp];
+        let lose_payoff = -contrib[p];
+        let tie_payoff = (win_payoff + lose_payoff)*0.5;
+        let s_h = &board.strengths[p];
+        let s_o = &board.strengths[opp];
+        let n_h = self.hands[p].len();
+        let mut win = vec![0.0; n_h];
+        let mut lose = vec![0.0; n_h];
+        // weaker opponent hands, swept from the bottom
+        let mut total = 0.0;
+        let mut per_card = [0.0f32; NUM_CARDS];
+        let mut j = 0;
+        for h in board.order[p].iter() {
+          while j < s_o.len() && s_o[board.order[opp][j]] < s_h[*h] {
+            let o = board.order[opp][j];
+            total += opp_reach[o];
+            per_card[self.hands[opp][o][0] as usize] += opp_reach[o];
+            per_card[self.hands[opp][o][1] as usize] += opp_reach[o];
+            j += 1;
+          }
+          let c = self.hands[p][*h];
+          win[*h] = total - per_card[c[0] as usize] - per_card[c[1] as usize];
+        }
+        // stronger opponent hands, swept from the top
+        let mut total = 0.0;
+        let mut per_card = [0.0f32; NUM_CARDS];
+        let mut j = s_o.len();
+        for h in board.order[p].iter().rev() {
+          while j > 0 && s_o[board.order[opp][j-1]] > s_h[*h] {
+            let o = board.order[opp][j-1];
+            total += opp_reach[o];
+            per_card[self.hands[opp][o][0] as usize] += opp_reach[o];
+            per_card[self.hands[opp][o][1] as usize] += opp_reach[o];
+            j -= 1;
+          }
+          let c = self.hands[p][*h];
+          lose[*h] = total - per_card[c[0] as usize] - per_card[c[1] as usize];
+        }
+        let all = unblocked_reach(&self.hands[p], opp_reach, &self.hands[opp], &self.same_combo[p]);
+        return (0..n_h).map(|h| {
+          let tie = (all[h] - win[h] - lose[h]).max(0.0);
+          win[h]*win_payoff + lose[h]*lose_payoff + tie*tie_payoff
+        }).collect()
+      },
+      _ => panic!("not a terminal node"),
+    }
+  }
+
+  // zero reach for combos that use the dealt card
+  fn remove_card(&self, reach: &[f32], player: usize, card: u8) -> Vec<f32> {
+    return reach.iter().zip(self.hands[player].iter()).map(|(r, h)| if h[0] == card || h[1] == card { 0.0 } else { *r }).collect()
+  }
+
+  // average of child values over the cards that can come. with both hands known 4 cards are out of the deck,
+  // so every pair of hands sees cards.len()-4 of the children
+  fn chance_values(&self, cards: &[u8], children: &[Node], p: usize, reach: &[Vec<f32>; 2], eval: &mut SubtreeValues) -> Vec<f32> {
+    let mut values = vec![0.0; self.hands[p].len()];
+    for (card, child) in cards.iter().zip(children.iter()) {
+      let child_reach = [self.remove_card(&reach[0], 0, *card), self.remove_card(&reach[1], 1, *card)];
+      let child_values = eval(child, &child_reach);
+      for (h, hand) in self.hands[p].iter().enumerate() {
+        if hand[0] != *card && hand[1] != *card {
+          values[h] += child_values[h];
+        }
+      }
+    }
+    let num_possible = (cards.len()-4) as f32;
+    return values.iter().map(|v| v/num_possible).collect()
+  }
+
+  fn cfr(&mut self, p: usize, iteration: usize, reach: &[Vec<f32>; 2]) -> Vec<f32> {
+    let mut root = std::mem::replace(&mut self.root, Node::Fold{folder: 0, contrib: [0.0, 0.0]});
+    let values = self.cfr_node(&mut root, p, iteration, reach);
+    self.root = root;
+    return values
+  }
+
+  fn cfr_node(&self, node: &mut Node, p: usize, iteration: usize, reach: &[Vec<f32>; 2]) -> Vec<f32> {
+    match node {
+      Node::Fold{..} | Node::Showdown{..} => return self.terminal_values(node, p, &reach[1-p]),
+      Node::Chance{cards, children} => {
+        let mut values = vec![0.0; self.hands[p].len()];
+        for (card, child) in cards.iter().zip(children.iter_mut()) {
+          let child_reach = [self.remove_card(&reach[0], 0, *card), self.remove_card(&reach[1], 1, *card)];
+          let child_values = self.cfr_node(child, p, iteration, &child_reach);
+          for (h, hand) in self.hands[p].iter().enumerate() {
+            if hand[0] != *card && hand[1] != *card {
+              values[h] += child_values[h];
+            }
+          }
+        }
+        let num_possible = (cards.len()-4) as f32;
+        return values.iter().map(|v| v/num_possible).collect()
+      },
+      Node::Decision(d) => {
+        let num_actions = d.actions.len();
+        let strategy = current_strategy(&d.regrets, num_actions);
+        let n = self.hands[d.player].len();
+        let mut values = vec![0.0; self.hands[p].len()];
+        let mut action_values: Vec<Vec<f32>> = Vec::new();
+        for a in 0..num_actions {
+          let mut child_reach = reach.clone();
+          for h in 0..n {
+            child_reach[d.player][h] *= strategy[h*num_actions+a];
+          }
+          let child_values = self.cfr_node(&mut d.children[a], p, iteration, &child_reach);
+          if d.player == p {
+            for h in 0..n {
+              values[h] += strategy[h*num_actions+a]*child_values[h];
+            }
+          } else {
+            for h in 0..values.len() {
+              values[h] += child_values[h];
+            }
+          }
+          action_values.push(child_values);
+        }
+        if d.player == p {
+          // CFR+: regrets never go below zero, average strategy weighted by iteration
+          for h in 0..n {
+            for (a, child_values) in action_values.iter().enumerate() {
+              let idx = h*num_actions+a;
+              d.regrets[idx] = (d.regrets[idx] + child_values[h] - values[h]).max(0.0);
+              d.strategy_sum[idx] += iteration as f32 * reach[p][h] * strategy[idx];
+            }
+          }
+        }
+        return values
+      },
+    }
+  }
+
+  // values of a best response of player p against opponent's average strategy
+  fn best_response(&self, node: &Node, p: usize, reach: &[Vec<f32>; 2]) -> Vec<f32> {
+    match node {
+      Node::Fold{..} | Node::Showdown{..} => return self.terminal_values(node, p, &reach[1-p]),
+      Node::Chance{cards, children} => {
+        return self.chance_values(cards, children, p, reach, &mut |child, child_reach| self.best_response(child, p, child_reach))
+      },
+      Node::Decision(d) => {
+        let num_actions = d.actions.len();
+        let n = self.hands[p].len();
+        if d.player == p {
+          let mut values = vec![f32::MIN; n];
+          for a in 0..num_actions {
+            let child_values = self.best_response(&d.children[a], p, reach);
+            for h in 0..n {
+              values[h] = values[h].max(child_values[h]);
+            }
+          }
+          return values
+        }
+        let strategy = d.average_strategy();
+        let mut values = vec![0.0; n];
+        for a in 0..num_actions {
+          let mut child_reach = reach.clone();
+          for h in 0..self.hands[d.player].len() {
+            child_reach[d.player][h] *= strategy[h*num_actions+a];
+          }
+          let child_values = self.best_response(&d.children[a], p, &child_reach);
+          for h in 0..n {
+            values[h] += child_values[h];
+          }
+        }
+        return values
+      },
+    }
+  }
+
+  // how much a best responding opponent wins on average against the current average strategies, in % of the pot
+  pub fn exploitability(&self) -> f32 {
+    let mut total = 0.0;
+    let mass: f32 = unblocked_reach(&self.hands[0], &self.initial_reach[1], &self.hands[1], &self.same_combo[0])
+      .iter().zip(self.initial_reach[0].iter()).map(|(u, r)| u*r).sum();
+    for p in 0..2 {
+      let values = self.best_response(&self.root, p, &self.initial_reach);
+      let value: f32 = values.iter().zip(self.initial_reach[p].iter()).map(|(v, r)| v*r).sum();
+      total += value/mass;
+    }
+    // the game is constant sum, both players together always win the starting pot
+    return (total - self.config.pot)*0.5/self.config.pot*100.0
+  }
+
+  // runs more iterations after `done` ones, so iteration weights keep growing across calls
+  pub fn solve(&mut self, done: usize, iterations: usize) {
+    let reach = self.initial_reach.clone();
+    for i in (done+1)..=(done+iterations) {
+      for p in 0..2 {
+        self.cfr(p, i, &reach);
+      }
+    }
+  }
+}
+
+// strategy of every combo at a decision, combos that use a card dealt on the way there are left out
+fn print_decision(subgame: &Subgame, d: &Decision, title: &str, dealt: &[u8]) {
+  let strategy = d.average_strategy();
+  let num_actions = d.actions.len();
+  let names: Vec<String> = d.actions.iter().map(|a| a.to_string()).collect();
+  println!("{} ({}):", title, names.join(" / "));
+  let reach = &subgame.initial_reach[d.player];
+  let live = |hand: &[u8; 2]| !dealt.contains(&hand[0]) && !dealt.contains(&hand[1]);
+  let total_reach: f32 = subgame.hands[d.player].iter().zip(reach.iter()).filter(|(hand, _)| live(hand)).map(|(_, r)| r).sum();
+  let mut overall = vec![0.0; num_actions];
+  for (h, hand) in subgame.hands[d.player].iter().enumerate() {
+    if !live(hand) {
+      continue;
+    }
+    let freqs: Vec<String> = (0..num_actions).map(|a| format!("{:5.1}%", strategy[h*num_actions+a]*100.0)).collect();
+    println!("  {}{}: {}", index_to_card(hand[1]), index_to_card(hand[0]), freqs.join(" "));
+    for a in 0..num_actions {
+      overall[a] += strategy[h*num_actions+a]*reach[h]/total_reach;
+    }
+  }
+  let freqs: Vec<String> = (0..num_actions).map(|a| format!("{:5.1}%", overall[a]*100.0)).collect();
+  println!("  {:<4}: {}", "all", freqs.join(" "));
+}
+
+// average strategy at every decision of the tree, titled by the actions and cards that lead to it
+fn print_tree(subgame: &Subgame, node: &Node, path: &str, dealt: &mut Vec<u8>) {
+  match node {
+    Node::Decision(d) => {
+      let player = if d.player == 0 { "OOP" } else { "IP" };
+      let title = match path.is_empty() {
+        true => format!("{} first action", player),
+        false => format!("{} after {}", player, path),
+      };
+      print_decision(subgame, d, &title, dealt);
+      for (a, child) in d.children.iter().enumerate() {
+        let step = format!("{} {}", player, d.actions[a]);
+        let next = if path.is_empty() { step } else { format!("{}, {}", path, step) };
+        print_tree(subgame, child, &next, dealt);
+      }
+    },
+    Node::Chance{cards, children} => {
+      for (card, child) in cards.iter().zip(children.iter()) {
+        dealt.push(*card);
+        print_tree(subgame, child, &format!("{}, {}", path, index_to_card(*card)), dealt);
+        dealt.pop();
+      }
+    },
+    Node::Fold{..} | Node::Showdown{..} => {},
+  }
+}
+
+pub fn run_solver(board: &[Card], oop_range: &PreflopRange, ip_range: &PreflopRange, config: SolverConfig, iterations: usize, score_table: &ScoreTable) {
+  let start_ts = Instant::now();
+  let mut subgame = Subgame::new(oop_range, ip_range, board, config, score_table);
+  println!("OOP combos: {}, IP combos: {}", subgame.hands[0].len(), subgame.hands[1].len());
+  let report_every = (iterations/5).max(1);
+  let mut done = 0;
+  while done < iterations {
+    let n = report_every.min(iterations-done);
+    subgame.solve(done, n);
+    done += n;
+    println!("Iteration {}: exploitability {:.3}% of pot ({:?})", done, subgame.exploitability(), start_ts.elapsed());
+  }
+  print_tree(&subgame, &subgame.root, "", &mut Vec::new());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::conv_string_to_cards;
+  use crate::score_table::tests::holdem_score_table;
+
+  #[test]
+  fn exploitability_falls_on_river() {
+    let config = SolverConfig{pot: 10.0, effective_stack: 10.0, bet_sizes: vec![1.0], raise_sizes: vec![1.0], max_bets_per_street: 1};
+    let oop: PreflopRange = "AA,KK,AK,JT".parse().unwrap();
+    let ip: PreflopRange = "QQ,AQ,KQ,T9".parse().unwrap();
+    let board = conv_string_to_cards("Ah 9d 7c 6s 6h");
+    let mut subgame = Subgame::new(&oop, &ip, &board, config, holdem_score_table());
+    subgame.solve(0, 10);
+    let early = subgame.exploitability();
+    subgame.solve(10, 490);
+    let late = subgame.exploitability();
+    assert!(late < early, "exploitability went from {} to {}", early, late);
+    assert!(late < 1.0, "exploitability {}% of pot after 500 iterations", late);
+  }
+}
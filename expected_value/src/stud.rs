@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::time::SystemTime;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use itertools::Itertools;
+use crate::{Card, HandRank};
+use crate::lowball::{LowKind, LowHand, EIGHT_OR_BETTER, best_low, hi_lo_shares};
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index, index_to_card};
+use crate::variant::{self, Variant};
+
+const EQUITY_SIMULATIONS: usize = 20000;
+// every player ends up with 7 cards
+const STUD_CARDS: usize = 7;
+// third to sixth street cards are dealt face up, the last one face down
+const MAX_UP_CARDS: usize = 4;
+
+pub struct Stud<'a> {
+  pub hi_lo: bool,
+  pub score_table: &'a ScoreTable,
+}
+
+// no board, all 7 cards belong to the player, so the best 5 of them is the same as in hold'em
+impl Variant for Stud<'_> {
+  fn name(&self) -> String {
+    return if self.hi_lo { "Stud-8".to_string() } else { "Stud".to_string() }
+  }
+
+  fn deck(&self) -> Vec<Card> {
+    return (0..NUM_CARDS as u8).map(index_to_card).collect()
+  }
+
+  fn num_hole_cards(&self) -> usize {
+    return STUD_CARDS
+  }
+
+  fn board_streets(&self) -> Vec<usize> {
+    return Vec::new()
+  }
+
+  fn score(&self, hole: &[Card], board: &[Card]) -> f32 {
+    let cards: Vec<u8> = hole.iter().chain(board.iter()).map(card_index).collect();
+    return self.score_table.best_score(&cards)
+  }
+
+  fn low(&self, hole: &[Card], board: &[Card]) -> Option<LowHand> {
+    if !self.hi_lo {
+      return None
+    }
+    let cards: Vec<u8> = hole.iter().chain(board.iter()).map(card_index).collect();
+    return best_low(&cards, LowKind::AceToFive, Some(EIGHT_OR_BETTER))
+  }
+
+  fn hi_lo(&self) -> bool {
+    return self.hi_lo
+  }
+}
+
+// Seven card stud player as the other players see them: down cards are only known for hero.
+pub struct StudPlayer {
+  pub down: Vec<Card>,
+  pub up: Vec<Card>,
+  pub folded: bool,
+}
+
+impl StudPlayer {
+  pub fn num_cards(&self) -> usize {
+    return self.down.len() + self.up.len()
+  }
+}
+
+// Everything seen at the table: hero, opponents' up cards and cards that are out of play,
+// like up cards of folded players or a card flashed by the dealer.
+pub struct StudState {
+  pub hero: StudPlayer,
+  pub opponents: Vec<StudPlayer>,
+  pub dead: Vec<Card>,
+  // Stud-8, pot is split with the best 8 or better low
+  pub hi_lo: bool,
+}
+
+impl StudState {
+  // every card hero can see, they can't come anymore
+  pub fn seen_cards(&self) -> Vec<Card> {
+    let mut cards = self.hero.down.clone();
+    cards.extend(self.hero.up.iter());
+    for o in &self.opponents {
+      cards.extend(o.up.iter());
+    }
+    cards.extend(self.dead.iter());
+    return cards
+  }
+
+  pub fn validate(&self) -> Result<(), Error> {
+    let seen = self.seen_cards();
+    if seen.iter().enumerate().any(|(i, c)| seen[i+1..].contains(c)) {
+      return Err(Error::new(ErrorKind::InvalidInput, "same card seen twice"))
+    }
+    if self.hero.num_cards() < 3 || self.hero.num_cards() > STUD_CARDS {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("hero needs 3 to 7 cards, got: {}", self.hero.num_cards())))
+    }
+    if let Some(o) = std::iter::once(&self.hero).chain(self.opponents.iter()).find(|o| o.up.len() > MAX_UP_CARDS) {
+      return Err(Error::new(ErrorKind::InvalidInput, format!("a player has at most {} up cards, got: {}", MAX_UP_CARDS, o.up.len())))
+    }
+    // cards still to come for hero and every opponent that is in the hand
+    let mut needed = STUD_CARDS - self.hero.num_cards();
+    for o in self.opponents.iter().filter(|o| !o.folded) {
+      needed += STUD_CARDS - o.up.len();
+    }
+    if needed > NUM_CARDS - seen.len() {
+      return Err(Error::new(ErrorKind::InvalidInput, "not enough cards left in the deck for everyone"))
+    }
+    return Ok(())
+  }
+}
+
+// hand class of 3 or 4 cards on early streets, only pairs and sets are possible there
+fn partial_rank(cards: &[Card]) -> HandRank {
+  let mut counts: HashMap<u8, u8> = HashMap::new();
+  for c in cards {
+    *counts.entry(c.rank).or_insert(0) += 1;
+  }
+  let mut groups: Vec<u8> = counts.values().cloned().collect();
+  groups.sort_by(|a, b| b.cmp(a));
+  return match (groups[0], groups.get(1)) {
+    (4, _) => HandRank::FourOfAKind,
+    (3, _) => HandRank::ThreeOfAKind,
+    (2, Some(2)) => HandRank::TwoPairs,
+    (2, _) => HandRank::Pair,
+    _ => HandRank::HighCard,
+  }
+}
+
+fn hand_rank(cards: &[Card], game: &Stud) -> HandRank {
+  if cards.len() < 5 {
+    return partial_rank(cards)
+  }
+  return game.rank_of_score(game.score(cards, &[]))
+}
+
+pub struct StudAnalysis {
+  pub rank: HandRank,
+  pub low: Option<LowHand>,
+  pub hi_lo: bool,
+  pub equity: f32,
+  // next card that improves hero's hand class. live ones can still come, dead ones were seen
+  pub live_outs: Vec<(Card, HandRank)>,
+  pub dead_outs: Vec<(Card, HandRank)>,
+}
+
+// Equity against the opponents still in the hand: their unseen cards and hero's next cards come
+// from what is left after removing every card hero has seen.
+pub fn analyze_stud(state: &StudState, score_table: &ScoreTable) -> StudAnalysis {
+  let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+  let seen = state.seen_cards();
+  let mut hero_cards: Vec<Card> = state.hero.down.clone();
+  hero_cards.extend(state.hero.up.iter());
+  let game = Stud{hi_lo: state.hi_lo, score_table: score_table};
+  let mut deck = variant::remaining_deck(&game, &seen);
+  let active: Vec<&StudPlayer> = state.opponents.iter().filter(|o| !o.folded).collect();
+
+  let mut won = 0.0;
+  for _ in 0..EQUITY_SIMULATIONS {
+    let mut next = 0;
+    let mut deal = |cards: &mut Vec<Card>, deck: &mut Vec<Card>| {
+      while cards.len() < STUD_CARDS {
+        let j = rng.gen_range(next..deck.len());
+        deck.swap(next, j);
+        cards.push(deck[next]);
+        next += 1;
+      }
+    };
+    let mut hands = vec![hero_cards.clone()];
+    deal(&mut hands[0], &mut deck);
+    for o in &active {
+      let mut cards = o.up.clone();
+      deal(&mut cards, &mut deck);
+      hands.push(cards);
+    }
+    let high: Vec<f32> = hands.iter().map(|h| game.score(h, &[])).collect();
+    let low: Vec<Option<LowHand>> = hands.iter().map(|h| game.low(h, &[])).collect();
+    won += hi_lo_shares(&high, &low)[0];
+  }
+
+  let rank = hand_rank(&hero_cards, &game);
+  let low = if hero_cards.len() >= 5 { game.low(&hero_cards, &[]) } else { None };
+  let mut live_outs = Vec::new();
+  let mut dead_outs = Vec::new();
+  if hero_cards.len() < STUD_CARDS {
+    let hero_seen: Vec<Card> = seen.iter().filter(|c| !hero_cards.contains(c)).cloned().collect();
+    for idx in 0..NUM_CARDS as u8 {
+      let card = index_to_card(idx);
+      if hero_cards.contains(&card) {
+        continue;
+      }
+      let mut cards = hero_cards.clone();
+      cards.push(card);
+      let next_rank = hand_rank(&cards, &game);
+      if next_rank <= rank {
+        continue;
+      }
+      if hero_seen.contains(&card) {
+        dead_outs.push((card, next_rank));
+      } else {
+        live_outs.push((card, next_rank));
+      }
+    }
+  }
+  return StudAnalysis{rank: rank, low: low, hi_lo: state.hi_lo, equity: won/EQUITY_SIMULATIONS as f32, live_outs: live_outs, dead_outs: dead_outs}
+}
+
+fn write_outs(f: &mut fmt::Formatter, outs: &[(Card, HandRank)]) -> fmt::Result {
+  for (rank, group) in &outs.iter().sorted_by_key(|o| (o.1, o.0)).group_by(|o| o.1) {
+    let cards: Vec<String> = group.map(|o| o.0.to_string()).collect();
+    writeln!(f, "  {:<18}:{}", rank.to_string(), cards.join(" "))?;
+  }
+  Ok(())
+}
+
+impl fmt::Display for StudAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "Stud{} hand: {}", if self.hi_lo { "-8" } else { "" }, self.rank)?;
+    if self.hi_lo {
+      match self.low {
+        Some(low) => writeln!(f, "Low: {}", low)?,
+        None => writeln!(f, "Low: none")?,
+      }
+    }
+    writeln!(f, "Hand Equity: {:.2}%", self.equity*100.0)?;
+    if !self.live_outs.is_empty() || !self.dead_outs.is_empty() {
+      writeln!(f, "Outs ({} live, {} already seen):", self.live_outs.len(), self.dead_outs.len())?;
+      write_outs(f, &self.live_outs)?;
+      if !self.dead_outs.is_empty() {
+        writeln!(f, "Dead outs:")?;
+        write_outs(f, &self.dead_outs)?;
+      }
+    }
+    Ok(())
+  }
+}
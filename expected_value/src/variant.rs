@@ -0,0 +1,235 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::time::SystemTime;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use itertools::Itertools;
+use crate::{Card, HandRank};
+use crate::lowball::{LowHand, hi_lo_shares};
+use crate::score_table::{ScoreTable, NUM_CARDS, card_index, index_to_card};
+use crate::omaha::Omaha;
+use crate::short_deck::ShortDeck;
+use crate::stud::Stud;
+
+const EQUITY_SIMULATIONS: usize = 20000;
+
+pub const STANDARD_ORDER: [HandRank; 10] = [
+  HandRank::HighCard,
+  HandRank::Pair,
+  HandRank::TwoPairs,
+  HandRank::ThreeOfAKind,
+  HandRank::Straight,
+  HandRank::Flush,
+  HandRank::FullHouse,
+  HandRank::FourOfAKind,
+  HandRank::StraightFlush,
+  HandRank::RoyalFlush,
+];
+
+// Rules of a poker game as far as equity is concerned: which cards are in play, how they are dealt and
+// how the best hand is made out of them. Betting structure is not part of it.
+// Used by the variant, simulate, omaha, shortdeck and stud modes. Hand analysis in once/loop, the bots and
+// ML data generation stay hold'em only, they are built on the 52 card combinations and starting hands tables.
+pub trait Variant {
+  fn name(&self) -> String;
+  fn deck(&self) -> Vec<Card>;
+  // cards each player gets for themselves: 2 in hold'em, 4 or 5 in Omaha, all 7 in stud
+  fn num_hole_cards(&self) -> usize;
+  // community cards dealt on each street, [3, 1, 1] for flop, turn and river. stud has no board
+  fn board_streets(&self) -> Vec<usize>;
+  // best high hand out of at least 5 cards, hundreds are the position of the hand class in hand_order()
+  fn score(&self, hole: &[Card], board: &[Card]) -> f32;
+  // best qualifying low in split pot games, None when there is none or the game is high only
+  fn low(&self, _hole: &[Card], _board: &[Card]) -> Option<LowHand> {
+    return None
+  }
+  fn hi_lo(&self) -> bool {
+    return false
+  }
+  // hand classes from worst to best
+  fn hand_order(&self) -> [HandRank; 10] {
+    return STANDARD_ORDER
+  }
+
+  fn board_size(&self) -> usize {
+    return self.board_streets().iter().sum()
+  }
+
+  fn rank_of_score(&self, score: f32) -> HandRank {
+    return self.hand_order()[(score/100.0) as usize]
+  }
+
+  fn strength(&self, rank: HandRank) -> usize {
+    return self.hand_order().iter().position(|r| *r == rank).unwrap()
+  }
+}
+
+pub struct Holdem<'a> {
+  pub score_table: &'a ScoreTable,
+}
+
+impl Variant for Holdem<'_> {
+  fn name(&self) -> String {
+    return "Hold'em".to_string()
+  }
+
+  fn deck(&self) -> Vec<Card> {
+    return (0..NUM_CARDS as u8).map(index_to_card).collect()
+  }
+
+  fn num_hole_cards(&self) -> usize {
+    return 2
+  }
+
+  fn board_streets(&self) -> Vec<usize> {
+    return vec![3, 1, 1]
+  }
+
+  // board can play on its own
+  fn score(&self, hole: &[Card], board: &[Card]) -> f32 {
+    let cards: Vec<u8> = hole.iter().chain(board.iter()).map(card_index).collect();
+    return self.score_table.best_score(&cards)
+  }
+}
+
+// holdem, shortdeck, plo4, plo5, plo8 (Omaha Hi-Lo), stud and stud8
+pub fn variant_from_name<'a>(name: &str, score_table: &'a ScoreTable) -> Result<Box<dyn Variant + 'a>, Error> {
+  return match name {
+    "holdem" => Ok(Box::new(Holdem{score_table: score_table})),
+    "shortdeck" => Ok(Box::new(ShortDeck{})),
+    "plo4" => Ok(Box::new(Omaha{num_hole_cards: 4, hi_lo: false, score_table: score_table})),
+    "plo5" => Ok(Box::new(Omaha{num_hole_cards: 5, hi_lo: false, score_table: score_table})),
+    "plo8" => Ok(Box::new(Omaha{num_hole_cards: 4, hi_lo: true, score_table: score_table})),
+    "stud" => Ok(Box::new(Stud{hi_lo: false, score_table: score_table})),
+    "stud8" => Ok(Box::new(Stud{hi_lo: true, score_table: score_table})),
+    _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown variant: {}", name))),
+  }
+}
+
+pub fn remaining_deck(variant: &dyn Variant, seen: &[Card]) -> Vec<Card> {
+  return variant.deck().into_iter().filter(|c| !seen.contains(c)).collect()
+}
+
+// Hero's share of the pot against num_opponents random hands. Hero's missing hole cards (stud streets to come),
+// the rest of the board and opponents' hands are dealt from what is left of the deck after dead cards.
+pub fn equity(variant: &dyn Variant, hole: &[Card], board: &[Card], dead: &[Card], num_opponents: usize, rng: &mut ChaCha20Rng) -> f32 {
+  let mut seen = hole.to_vec();
+  seen.extend(board.iter());
+  seen.extend(dead.iter());
+  let mut deck = remaining_deck(variant, &seen);
+  let n = variant.num_hole_cards();
+  let hero_needed = n - hole.len();
+  let board_needed = variant.board_size() - board.len();
+  let needed = hero_needed + board_needed + num_opponents*n;
+  let mut won = 0.0;
+  for _ in 0..EQUITY_SIMULATIONS {
+    for i in 0..needed {
+      let j = rng.gen_range(i..deck.len());
+      deck.swap(i, j);
+    }
+    let mut hero = hole.to_vec();
+    hero.extend(&deck[..hero_needed]);
+    let mut full_board = board.to_vec();
+    full_board.extend(&deck[hero_needed..hero_needed+board_needed]);
+    let mut hands = vec![hero];
+    for o in 0..num_opponents {
+      let start = hero_needed + board_needed + o*n;
+      hands.push(deck[start..start+n].to_vec());
+    }
+    let high: Vec<f32> = hands.iter().map(|h| variant.score(h, &full_board)).collect();
+    let low: Vec<Option<LowHand>> = hands.iter().map(|h| variant.low(h, &full_board)).collect();
+    won += hi_lo_shares(&high, &low)[0];
+  }
+  return won/EQUITY_SIMULATIONS as f32
+}
+
+pub struct VariantAnalysis {
+  pub name: String,
+  pub hi_lo: bool,
+  // None until hero has 5 cards to make a hand with
+  pub rank: Option<HandRank>,
+  pub low: Option<LowHand>,
+  pub equity: f32,
+  // hand class of a random opponent hand on the current board and its share, from worst to best
+  pub opponent_ranks: Vec<(HandRank, f32)>,
+  // next board card that improves hero's hand class, by the class it makes
+  pub outs: Vec<(Card, HandRank)>,
+}
+
+// equity, opponent hand classes and outs of hero's hand in any variant. dead cards are burned or exposed,
+// so they are neither outs nor in opponent hands
+pub fn analyze_variant(variant: &dyn Variant, hole: &[Card], board: &[Card], dead: &[Card], num_opponents: usize) -> VariantAnalysis {
+  let mut rng = ChaCha20Rng::seed_from_u64(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs());
+  let complete = hole.len() + board.len() >= 5 && (board.len() >= 3 || variant.board_size() == 0);
+  let rank = if complete { Some(variant.rank_of_score(variant.score(hole, board))) } else { None };
+  let low = if complete { variant.low(hole, board) } else { None };
+  let equity = equity(variant, hole, board, dead, num_opponents, &mut rng);
+
+  let mut seen = hole.to_vec();
+  seen.extend(board.iter());
+  seen.extend(dead.iter());
+  let mut deck = remaining_deck(variant, &seen);
+  let n = variant.num_hole_cards();
+  let mut opponent_ranks = Vec::new();
+  // opponent's cards are all unknown in stud, so there is no current hand to look at
+  if board.len() >= 3 {
+    let mut counts = [0; 10];
+    for _ in 0..EQUITY_SIMULATIONS {
+      for i in 0..n {
+        let j = rng.gen_range(i..deck.len());
+        deck.swap(i, j);
+      }
+      counts[(variant.score(&deck[..n], board)/100.0) as usize] += 1;
+    }
+    let order = variant.hand_order();
+    for (i, count) in counts.iter().enumerate().filter(|(_, c)| **c > 0) {
+      opponent_ranks.push((order[i], *count as f32/EQUITY_SIMULATIONS as f32));
+    }
+  }
+
+  let mut outs = Vec::new();
+  if let Some(rank) = rank {
+    if board.len() >= 3 && board.len() < variant.board_size() {
+      for card in &deck {
+        let mut next_board = board.to_vec();
+        next_board.push(*card);
+        let next_rank = variant.rank_of_score(variant.score(hole, &next_board));
+        if variant.strength(next_rank) > variant.strength(rank) {
+          outs.push((*card, next_rank));
+        }
+      }
+      outs.sort_by(|a, b| variant.strength(a.1).cmp(&variant.strength(b.1)).then(a.0.cmp(&b.0)));
+    }
+  }
+  return VariantAnalysis{name: variant.name(), hi_lo: variant.hi_lo(), rank: rank, low: low, equity: equity, opponent_ranks: opponent_ranks, outs: outs}
+}
+
+impl fmt::Display for VariantAnalysis {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.rank {
+      Some(rank) => writeln!(f, "{} hand: {}", self.name, rank)?,
+      None => writeln!(f, "{} hand: not made yet", self.name)?,
+    }
+    if self.hi_lo {
+      match self.low {
+        Some(low) => writeln!(f, "Low: {}", low)?,
+        None => writeln!(f, "Low: none")?,
+      }
+    }
+    writeln!(f, "Hand Equity: {:.2}%", self.equity*100.0)?;
+    if !self.opponent_ranks.is_empty() {
+      writeln!(f, "Opponent hand range:")?;
+      for (rank, share) in &self.opponent_ranks {
+        writeln!(f, "{:<20}:{:.1}%", rank.to_string(), share*100.0)?;
+      }
+    }
+    if !self.outs.is_empty() {
+      writeln!(f, "Outs ({}):", self.outs.len())?;
+      for (rank, group) in &self.outs.iter().group_by(|o| o.1) {
+        let cards: Vec<String> = group.map(|o| o.0.to_string()).collect();
+        writeln!(f, "{:<20}:{}", rank.to_string(), cards.join(" "))?;
+      }
+    }
+    Ok(())
+  }
+}